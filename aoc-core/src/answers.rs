@@ -0,0 +1,195 @@
+use crate::{Part, Report};
+use anyhow::{anyhow, Error, Result};
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// Answers expected from a day, stored next to its input as `part1 = "..."` and `part2 = "..."` lines.
+///
+/// The format is a subset of TOML, values may also be written without quotes. Lines starting with `#` are comments.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Answers {
+    pub part1: Option<String>,
+    pub part2: Option<String>,
+}
+
+/// Part whose answer differs from the expected one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Mismatch {
+    pub part: Part,
+    /// `None` when no answer was recorded for the part.
+    pub expected: Option<String>,
+    pub actual: String,
+}
+
+impl Answers {
+    /// Takes the answers of the parts in `report` as the expected ones.
+    pub fn of(report: &Report) -> Self {
+        let mut answers = Answers::default();
+        for answer in report.answers.iter() {
+            *answers.part_mut(answer.part) = Some(answer.value.clone());
+        }
+
+        answers
+    }
+
+    pub fn get(&self, part: Part) -> Option<&str> {
+        match part {
+            Part::One => self.part1.as_deref(),
+            Part::Two => self.part2.as_deref(),
+        }
+    }
+
+    fn part_mut(&mut self, part: Part) -> &mut Option<String> {
+        match part {
+            Part::One => &mut self.part1,
+            Part::Two => &mut self.part2,
+        }
+    }
+
+    /// Compares answers in `report` with the expected ones, parts missing from the report are not compared.
+    pub fn diff(&self, report: &Report) -> Vec<Mismatch> {
+        report
+            .answers
+            .iter()
+            .filter(|answer| self.get(answer.part) != Some(answer.value.as_str()))
+            .map(|answer| Mismatch {
+                part: answer.part,
+                expected: self.get(answer.part).map(str::to_string),
+                actual: answer.value.clone(),
+            })
+            .collect()
+    }
+}
+
+impl FromStr for Answers {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut answers = Answers::default();
+
+        for (idx, line) in s.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once('=') else {
+                return Err(anyhow!(
+                    "line {}: expected key = value, found '{}'",
+                    idx + 1,
+                    line
+                ));
+            };
+            let part = match key.trim() {
+                "part1" => Part::One,
+                "part2" => Part::Two,
+                key => Err(anyhow!(
+                    "line {}: expected part1 or part2, found '{}'",
+                    idx + 1,
+                    key
+                ))?,
+            };
+            let value = value.trim();
+            let value = value
+                .strip_prefix('"')
+                .and_then(|v| v.strip_suffix('"'))
+                .unwrap_or(value);
+
+            *answers.part_mut(part) = Some(value.to_string());
+        }
+
+        Ok(answers)
+    }
+}
+
+impl Display for Answers {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for part in Part::ALL {
+            if let Some(value) = self.get(part) {
+                writeln!(f, "part{} = \"{}\"", part, value)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for Mismatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "part {}:", self.part)?;
+        match &self.expected {
+            Some(expected) => writeln!(f, "- {}", expected)?,
+            None => writeln!(f, "- (not recorded)")?,
+        }
+        write!(f, "+ {}", self.actual)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Answers, Mismatch};
+    use crate::{Answer, Part, Report};
+    use std::time::Duration;
+
+    fn report(answers: &[(Part, &str)]) -> Report {
+        Report {
+            day: 5,
+            parse_elapsed: Duration::ZERO,
+            answers: answers
+                .iter()
+                .map(|&(part, value)| Answer {
+                    part,
+                    value: value.to_string(),
+                    elapsed: Duration::ZERO,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn answers_parse_test() {
+        let answers: Answers = "# day 5\npart1 = \"313045984\"\n\n  part2=20283860\n"
+            .parse()
+            .unwrap();
+
+        assert_eq!(
+            answers,
+            Answers {
+                part1: Some("313045984".to_string()),
+                part2: Some("20283860".to_string()),
+            }
+        );
+        assert_eq!(answers.to_string().parse::<Answers>().unwrap(), answers);
+        assert!("part3 = 1".parse::<Answers>().is_err());
+        assert!("part1 1".parse::<Answers>().is_err());
+    }
+
+    #[test]
+    fn answers_diff_test() {
+        let answers = Answers {
+            part1: Some("35".to_string()),
+            part2: None,
+        };
+
+        assert_eq!(answers.diff(&report(&[(Part::One, "35")])), vec![]);
+        assert_eq!(
+            answers.diff(&report(&[(Part::One, "36"), (Part::Two, "46")])),
+            vec![
+                Mismatch {
+                    part: Part::One,
+                    expected: Some("35".to_string()),
+                    actual: "36".to_string(),
+                },
+                Mismatch {
+                    part: Part::Two,
+                    expected: None,
+                    actual: "46".to_string(),
+                }
+            ]
+        );
+        assert_eq!(
+            Answers::of(&report(&[(Part::Two, "46")])).to_string(),
+            "part2 = \"46\"\n"
+        );
+    }
+}
@@ -0,0 +1,173 @@
+mod answers;
+
+pub use answers::{Answers, Mismatch};
+use anyhow::{anyhow, Result};
+use std::fmt::Display;
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Solution of a puzzle, parsing its input once and answering both parts from it.
+pub trait Solver {
+    /// Day of December the puzzle was given on.
+    const DAY: u8;
+
+    type Input;
+    type Answer1: Display;
+    type Answer2: Display;
+
+    fn parse(input: &str) -> Result<Self::Input>;
+    fn part1(input: &Self::Input) -> Result<Self::Answer1>;
+    fn part2(input: &Self::Input) -> Result<Self::Answer2>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl Part {
+    pub const ALL: [Part; 2] = [Part::One, Part::Two];
+}
+
+impl Display for Part {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Part::One => write!(f, "1"),
+            Part::Two => write!(f, "2"),
+        }
+    }
+}
+
+/// Answer to a part of the puzzle along with the time it took, parsing not included.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Answer {
+    pub part: Part,
+    pub value: String,
+    pub elapsed: Duration,
+}
+
+/// Outcome of running a puzzle, printed the same way for every day.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Report {
+    pub day: u8,
+    pub parse_elapsed: Duration,
+    pub answers: Vec<Answer>,
+}
+
+impl Display for Report {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Day {} input parsed in {:.3?}",
+            self.day, self.parse_elapsed
+        )?;
+        for answer in self.answers.iter() {
+            write!(
+                f,
+                "\nDay {} part {}: {} ({:.3?})",
+                self.day, answer.part, answer.value, answer.elapsed
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Object safe view of a `Solver`, so that days with different input and answer types can be run alike.
+pub trait Puzzle {
+    fn day(&self) -> u8;
+
+    /// Parses `input` and answers `parts` of the puzzle, timing every step.
+    fn run(&self, input: &str, parts: &[Part]) -> Result<Report>;
+}
+
+impl<S: Solver> Puzzle for S {
+    fn day(&self) -> u8 {
+        S::DAY
+    }
+
+    fn run(&self, input: &str, parts: &[Part]) -> Result<Report> {
+        let started = Instant::now();
+        let parsed = S::parse(input)?;
+        let parse_elapsed = started.elapsed();
+
+        let mut answers = vec![];
+        for &part in parts {
+            let started = Instant::now();
+            let value = match part {
+                Part::One => S::part1(&parsed)?.to_string(),
+                Part::Two => S::part2(&parsed)?.to_string(),
+            };
+
+            answers.push(Answer {
+                part,
+                value,
+                elapsed: started.elapsed(),
+            });
+        }
+
+        Ok(Report {
+            day: S::DAY,
+            parse_elapsed,
+            answers,
+        })
+    }
+}
+
+/// Answers both parts of the puzzle for the input in the file at `path`.
+pub fn solve_file(puzzle: &dyn Puzzle, path: impl AsRef<Path>) -> Result<Report> {
+    let path = path.as_ref();
+    let input = fs::read_to_string(path)
+        .map_err(|err| anyhow!("Cannot read {}: {}", path.display(), err))?;
+
+    puzzle.run(&input, &Part::ALL)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Part, Puzzle, Solver};
+    use anyhow::Result;
+
+    struct Sums;
+
+    impl Solver for Sums {
+        const DAY: u8 = 25;
+
+        type Input = Vec<i32>;
+        type Answer1 = i32;
+        type Answer2 = usize;
+
+        fn parse(input: &str) -> Result<Self::Input> {
+            Ok(input
+                .split_whitespace()
+                .map(str::parse)
+                .collect::<Result<Vec<i32>, _>>()?)
+        }
+
+        fn part1(input: &Self::Input) -> Result<Self::Answer1> {
+            Ok(input.iter().sum())
+        }
+
+        fn part2(input: &Self::Input) -> Result<Self::Answer2> {
+            Ok(input.len())
+        }
+    }
+
+    #[test]
+    fn puzzle_run_test() {
+        let report = Sums.run("1 2 -7", &[Part::Two, Part::One]).unwrap();
+        let answers: Vec<(Part, &str)> = report
+            .answers
+            .iter()
+            .map(|answer| (answer.part, answer.value.as_str()))
+            .collect();
+
+        assert_eq!(report.day, 25);
+        assert_eq!(answers, vec![(Part::Two, "3"), (Part::One, "-4")]);
+        assert!(report.to_string().contains("\nDay 25 part 1: -4 ("));
+
+        assert!(Sums.run("1 two", &Part::ALL).is_err());
+    }
+}
@@ -0,0 +1,259 @@
+use aoc_core::Solver;
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use day5::brute_force;
+use day5::generator::{self, GeneratorConfig, Random};
+use day5::Id;
+use indicatif::ProgressBar;
+use std::fs;
+use std::hint::black_box;
+use std::num::NonZeroUsize;
+use std::ops::Range;
+use std::path::Path;
+use std::sync::atomic::AtomicBool;
+use std::thread;
+
+/// Seed of the generator shared with day5 almanacs, so that synthetic inputs are the same on every run.
+const SEED: u64 = 2023;
+
+/// Random number below `bound`.
+fn below(random: &mut Random, bound: u64) -> u64 {
+    random.next_u64() % bound
+}
+
+/// `count` distinct numbers from `1..=max`, none of them among `except`.
+fn distinct(random: &mut Random, count: usize, max: u64, except: &[u64]) -> Vec<u64> {
+    let mut numbers = vec![];
+    while numbers.len() < count {
+        let number = 1 + below(random, max);
+        if !numbers.contains(&number) && !except.contains(&number) {
+            numbers.push(number);
+        }
+    }
+
+    numbers
+}
+
+fn real_input(day: u8) -> String {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .expect("aoc is a member of the workspace")
+        .join(format!("day{}", day))
+        .join("input.txt");
+
+    fs::read_to_string(&path)
+        .unwrap_or_else(|err| panic!("Cannot read {}: {}", path.display(), err))
+}
+
+/// Lines of letters, digits and digits spelled out, with at least one digit in every line.
+fn synthetic_day1(lines: usize) -> String {
+    const WORDS: [&str; 9] = [
+        "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
+    ];
+
+    let mut random = Random::new(SEED);
+    let mut input = String::new();
+    for _ in 0..lines {
+        let tokens = 4 + below(&mut random, 12);
+        let digit_at = below(&mut random, tokens);
+        for token in 0..tokens {
+            match below(&mut random, 3) {
+                _ if token == digit_at => input.push_str(&(1 + below(&mut random, 9)).to_string()),
+                0 => input.push_str(WORDS[below(&mut random, 9) as usize]),
+                _ => input.push((b'a' + below(&mut random, 26) as u8) as char),
+            }
+        }
+        input.push('\n');
+    }
+
+    input
+}
+
+/// Games of up to 6 turns, drawing up to 20 cubes of every color.
+fn synthetic_day2(games: usize) -> String {
+    const COLORS: [&str; 3] = ["red", "green", "blue"];
+
+    let mut random = Random::new(SEED);
+    let mut input = String::new();
+    for id in 1..=games {
+        let turns: Vec<String> = (0..1 + below(&mut random, 6))
+            .map(|_| {
+                let colors = 1 + below(&mut random, 3) as usize;
+                distinct(&mut random, colors, 3, &[])
+                    .iter()
+                    .map(|&color| {
+                        let amount = 1 + below(&mut random, 20);
+                        format!("{} {}", amount, COLORS[color as usize - 1])
+                    })
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            })
+            .collect();
+        input.push_str(&format!("Game {}: {}\n", id, turns.join("; ")));
+    }
+
+    input
+}
+
+/// Square schematic of numbers up to 3 digits long scattered among symbols and dots.
+fn synthetic_day3(size: usize) -> String {
+    const SYMBOLS: &[u8] = b"*#+$/=@%&-";
+
+    let mut random = Random::new(SEED);
+    let mut input = String::new();
+    for _ in 0..size {
+        let mut line = String::new();
+        while line.len() < size {
+            match below(&mut random, 10) {
+                0..=1 => {
+                    for _ in 0..1 + below(&mut random, 3) {
+                        line.push((b'1' + below(&mut random, 9) as u8) as char);
+                    }
+                    line.push('.');
+                }
+                2 => line.push(SYMBOLS[below(&mut random, SYMBOLS.len() as u64) as usize] as char),
+                _ => line.push('.'),
+            }
+        }
+        line.truncate(size);
+        input.push_str(&line);
+        input.push('\n');
+    }
+
+    input
+}
+
+/// Cards with 10 winning and 25 available numbers.
+fn synthetic_day4(cards: usize) -> String {
+    let mut random = Random::new(SEED);
+    let mut input = String::new();
+    for id in 1..=cards {
+        // 0.8 matching numbers on average, so that the number of copies stays bounded
+        let matching = match below(&mut random, 5) {
+            0..=1 => 0,
+            2..=3 => 1,
+            _ => 2,
+        };
+
+        let winning = distinct(&mut random, 10, 99, &[]);
+        let mut available = winning[..matching].to_vec();
+        available.extend(distinct(&mut random, 25 - matching, 99, &winning));
+
+        let numbers = |numbers: &[u64]| {
+            numbers
+                .iter()
+                .map(|n| format!("{:2}", n))
+                .collect::<Vec<String>>()
+                .join(" ")
+        };
+        input.push_str(&format!(
+            "Card {}: {} | {}\n",
+            id,
+            numbers(&winning),
+            numbers(&available)
+        ));
+    }
+
+    input
+}
+
+/// Almanac with more ranges than the real one, so that composing maps dominates.
+fn synthetic_day5() -> String {
+    generator::generate(&GeneratorConfig {
+        seed: SEED,
+        ranges_per_map: 200,
+        ..GeneratorConfig::default()
+    })
+    .expect("default generator settings are valid")
+    .text
+}
+
+/// Benchmarks parsing and both parts of the puzzle of `S` separately, for every one of named `inputs`.
+///
+/// Results are identified as `dayN/step/input`, e.g. `day5/part2/real`.
+fn bench_solver<S: Solver>(c: &mut Criterion, inputs: &[(&str, String)]) {
+    let mut group = c.benchmark_group(format!("day{}", S::DAY));
+    // some parts take most of a second, the default of 100 samples would take minutes
+    group.sample_size(10);
+
+    for (name, input) in inputs {
+        let parsed = S::parse(input).unwrap();
+
+        group.bench_with_input(BenchmarkId::new("parse", name), input, |b, input| {
+            b.iter(|| S::parse(black_box(input)).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("part1", name), &parsed, |b, parsed| {
+            b.iter(|| S::part1(black_box(parsed)).unwrap())
+        });
+        group.bench_with_input(BenchmarkId::new("part2", name), &parsed, |b, parsed| {
+            b.iter(|| S::part2(black_box(parsed)).unwrap())
+        });
+    }
+
+    group.finish();
+}
+
+/// Benchmarks mapping every single seed of a fixed seed set, on one thread and on all of them.
+fn bench_brute_force(c: &mut Criterion) {
+    let almanac = day5::Day5::parse(&real_input(5)).unwrap();
+    let maps = almanac.chain("seed", "location").unwrap();
+    let seed_ranges: Vec<Range<Id>> = vec![0..100_000, 1_000_000_000..1_000_100_000];
+    let cancelled = AtomicBool::new(false);
+    let progress = ProgressBar::hidden();
+
+    let mut group = c.benchmark_group("day5");
+    group.sample_size(10);
+    let mut thread_counts = vec![
+        NonZeroUsize::MIN,
+        thread::available_parallelism().unwrap_or(NonZeroUsize::MIN),
+    ];
+    thread_counts.dedup();
+    for threads in thread_counts {
+        group.bench_with_input(
+            BenchmarkId::new("brute_force_threads", threads),
+            &threads,
+            |b, &threads| {
+                b.iter(|| {
+                    brute_force::min_location(&maps, &seed_ranges, threads, &cancelled, &progress)
+                        .unwrap()
+                })
+            },
+        );
+    }
+
+    group.finish();
+}
+
+fn bench_days(c: &mut Criterion) {
+    bench_solver::<day1::Day1>(
+        c,
+        &[
+            ("real", real_input(1)),
+            ("synthetic", synthetic_day1(10_000)),
+        ],
+    );
+    bench_solver::<day2::Day2>(
+        c,
+        &[
+            ("real", real_input(2)),
+            ("synthetic", synthetic_day2(10_000)),
+        ],
+    );
+    bench_solver::<day3::Day3>(
+        c,
+        &[("real", real_input(3)), ("synthetic", synthetic_day3(400))],
+    );
+    bench_solver::<day4::Day4>(
+        c,
+        &[
+            ("real", real_input(4)),
+            ("synthetic", synthetic_day4(5_000)),
+        ],
+    );
+    bench_solver::<day5::Day5>(
+        c,
+        &[("real", real_input(5)), ("synthetic", synthetic_day5())],
+    );
+}
+
+criterion_group!(benches, bench_days, bench_brute_force);
+criterion_main!(benches);
@@ -0,0 +1,113 @@
+use aoc_core::Puzzle;
+
+/// Puzzles of every day, the puzzle of day `n` at index `n - 1`.
+pub const DAYS: [&dyn Puzzle; 5] = [
+    &day1::Day1,
+    &day2::Day2,
+    &day3::Day3,
+    &day4::Day4,
+    &day5::Day5,
+];
+
+#[cfg(test)]
+mod test {
+    use super::DAYS;
+    use aoc_core::Part;
+
+    const EXAMPLES: [(&str, &str, &str); 5] = [
+        (
+            "1abc2
+pqr3stu8vwx
+a1b2c3d4e5f
+treb7uchet",
+            "142",
+            "142",
+        ),
+        (
+            "Game 1: 3 blue, 4 red; 1 red, 2 green, 6 blue; 2 green
+Game 2: 1 blue, 2 green; 3 green, 4 blue, 1 red; 1 green, 1 blue
+Game 3: 8 green, 6 blue, 20 red; 5 blue, 4 red, 13 green; 5 green, 1 red
+Game 4: 1 green, 3 red, 6 blue; 3 green, 6 red; 3 green, 15 blue, 14 red
+Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green",
+            "8",
+            "2286",
+        ),
+        (
+            "467..114..
+...*......
+..35..633.
+......#...
+617*......
+.....+.58.
+..592.....
+......755.
+...$.*....
+.664.598..",
+            "4361",
+            "467835",
+        ),
+        (
+            "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
+Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
+Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11",
+            "13",
+            "30",
+        ),
+        (
+            "seeds: 79 14 55 13
+
+seed-to-soil map:
+50 98 2
+52 50 48
+
+soil-to-fertilizer map:
+0 15 37
+37 52 2
+39 0 15
+
+fertilizer-to-water map:
+49 53 8
+0 11 42
+42 0 7
+57 7 4
+
+water-to-light map:
+88 18 7
+18 25 70
+
+light-to-temperature map:
+45 77 23
+81 45 19
+68 64 13
+
+temperature-to-humidity map:
+0 69 1
+1 0 69
+
+humidity-to-location map:
+60 56 37
+56 93 4
+",
+            "35",
+            "46",
+        ),
+    ];
+
+    #[test]
+    fn days_examples_test() {
+        for (idx, (input, part1, part2)) in EXAMPLES.iter().enumerate() {
+            let report = DAYS[idx].run(input, &Part::ALL).unwrap();
+            let answers: Vec<&str> = report
+                .answers
+                .iter()
+                .map(|answer| answer.value.as_str())
+                .collect();
+
+            assert_eq!(report.day as usize, idx + 1);
+            assert_eq!(answers, vec![*part1, *part2], "day {}", idx + 1);
+        }
+    }
+}
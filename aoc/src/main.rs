@@ -0,0 +1,133 @@
+mod days;
+
+use crate::days::DAYS;
+use anyhow::{anyhow, Result};
+use aoc_core::{Answers, Part};
+use clap::{Parser, Subcommand};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+const ANSWERS: &str = "answers.toml";
+
+#[derive(Parser)]
+#[command(about = "Runs Advent of Code 2023 solutions")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Solve the puzzle of a day, printing answers along with the time they took
+    Run {
+        #[arg(value_parser = clap::value_parser!(u8).range(1..=DAYS.len() as i64))]
+        day: u8,
+
+        /// Solve only this part of the puzzle
+        #[arg(long, value_parser = clap::value_parser!(u8).range(1..=2))]
+        part: Option<u8>,
+
+        /// Read the puzzle input from this file instead of input.txt in the directory of the day
+        #[arg(long, value_name = "PATH")]
+        input: Option<PathBuf>,
+    },
+    /// Check that days still give the answers recorded in answers.toml next to their input, all days by default
+    Verify {
+        #[arg(value_parser = clap::value_parser!(u8).range(1..=DAYS.len() as i64))]
+        days: Vec<u8>,
+
+        /// Record the current answers as the expected ones instead of checking them
+        #[arg(long)]
+        record: bool,
+    },
+}
+
+/// Directory of a day in the workspace, found wherever the runner is started from.
+fn day_dir(day: u8) -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR"))
+        .parent()
+        .expect("aoc is a member of the workspace")
+        .join(format!("day{}", day))
+}
+
+fn read(path: &Path) -> Result<String> {
+    fs::read_to_string(path).map_err(|err| anyhow!("Cannot read {}: {}", path.display(), err))
+}
+
+/// Checks or records answers of `days`, returning whether all of them match the recorded ones.
+fn verify(days: &[u8], record: bool) -> Result<bool> {
+    let mut all_match = true;
+
+    for &day in days {
+        let input = read(&day_dir(day).join("input.txt"))?;
+        let report = DAYS[day as usize - 1].run(&input, &Part::ALL)?;
+        let answers_path = day_dir(day).join(ANSWERS);
+
+        if record {
+            fs::write(&answers_path, Answers::of(&report).to_string())?;
+            println!(
+                "Day {}: recorded answers to {}",
+                day,
+                answers_path.display()
+            );
+            continue;
+        }
+
+        if !answers_path.exists() {
+            all_match = false;
+            println!(
+                "Day {}: no answers recorded in {}, run verify --record to record them",
+                day,
+                answers_path.display()
+            );
+            continue;
+        }
+
+        let expected: Answers = read(&answers_path)?
+            .parse()
+            .map_err(|err| anyhow!("{}: {}", answers_path.display(), err))?;
+        let mismatches = expected.diff(&report);
+        if mismatches.is_empty() {
+            println!("Day {}: ok", day);
+        } else {
+            all_match = false;
+            println!(
+                "Day {}: answers differ from {}",
+                day,
+                answers_path.display()
+            );
+            for mismatch in mismatches {
+                println!("{}", mismatch);
+            }
+        }
+    }
+
+    Ok(all_match)
+}
+
+fn main() -> Result<()> {
+    match Cli::parse().command {
+        Command::Run { day, part, input } => {
+            let input = read(&input.unwrap_or_else(|| day_dir(day).join("input.txt")))?;
+
+            let parts = match part {
+                Some(1) => vec![Part::One],
+                Some(_) => vec![Part::Two],
+                None => Part::ALL.to_vec(),
+            };
+            println!("{}", DAYS[day as usize - 1].run(&input, &parts)?);
+        }
+        Command::Verify { mut days, record } => {
+            if days.is_empty() {
+                days = (1..=DAYS.len() as u8).collect();
+            }
+
+            if !verify(&days, record)? {
+                process::exit(1);
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,134 @@
+use anyhow::{anyhow, Result};
+use aoc_core::Solver;
+use std::cmp::{max, min};
+use std::collections::HashMap;
+
+/// Reads the calibration value from the first and last digits of the line, `None` if there are no digits.
+pub fn get_calibration_value_v1(s: &str) -> Option<u32> {
+    let mut digits = vec![];
+    for ch in s.chars() {
+        if ch.is_ascii_digit() {
+            digits.push(ch.to_digit(10).unwrap());
+        }
+    }
+
+    Some(10 * digits.first()? + digits.last()?)
+}
+
+/// Reads the calibration value like `get_calibration_value_v1`, digits spelled out with letters included.
+pub fn get_calibration_value_v2(s: &str) -> Option<u32> {
+    let translate_table = HashMap::from([
+        ("1", 1),
+        ("one", 1),
+        ("2", 2),
+        ("two", 2),
+        ("3", 3),
+        ("three", 3),
+        ("4", 4),
+        ("four", 4),
+        ("5", 5),
+        ("five", 5),
+        ("6", 6),
+        ("six", 6),
+        ("7", 7),
+        ("seven", 7),
+        ("8", 8),
+        ("eight", 8),
+        ("9", 9),
+        ("nine", 9),
+    ]);
+
+    let mut base_idx = 0;
+    let mut first = None;
+    let mut last = None;
+
+    while (first.is_none() || last.is_none()) && base_idx < s.len() {
+        for kv in translate_table.iter() {
+            if first.is_none() {
+                let compare_window = &s[base_idx..min(base_idx + kv.0.len(), s.len())];
+
+                if compare_window == *kv.0 {
+                    first = Some(kv.1);
+                }
+            }
+
+            if last.is_none() {
+                let compare_window = &s[max(
+                    0,
+                    s.len().saturating_sub(kv.0.len()).saturating_sub(base_idx),
+                )..s.len().saturating_sub(base_idx)];
+
+                if compare_window == *kv.0 {
+                    last = Some(kv.1);
+                }
+            }
+        }
+
+        base_idx += 1;
+    }
+
+    Some(10 * first? + last?)
+}
+
+/// Sums calibration values of every line of the document, read with `get_value`.
+pub fn calc_calibration_sum(
+    document: &[String],
+    get_value: fn(&str) -> Option<u32>,
+) -> Result<u32> {
+    document
+        .iter()
+        .map(|line| get_value(line).ok_or(anyhow!("No calibration value in line '{}'", line)))
+        .sum()
+}
+
+pub struct Day1;
+
+impl Solver for Day1 {
+    const DAY: u8 = 1;
+
+    type Input = Vec<String>;
+    type Answer1 = u32;
+    type Answer2 = u32;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        Ok(input.lines().map(str::to_string).collect())
+    }
+
+    fn part1(document: &Self::Input) -> Result<Self::Answer1> {
+        calc_calibration_sum(document, get_calibration_value_v1)
+    }
+
+    fn part2(document: &Self::Input) -> Result<Self::Answer2> {
+        calc_calibration_sum(document, get_calibration_value_v2)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn get_calibration_value_v1_test() {
+        assert_eq!(super::get_calibration_value_v1("1abc2"), Some(12));
+        assert_eq!(super::get_calibration_value_v1("pqr3stu8vwx"), Some(38));
+        assert_eq!(super::get_calibration_value_v1("a1b2c3d4e5f"), Some(15));
+        assert_eq!(super::get_calibration_value_v1("treb7uchet"), Some(77));
+        assert_eq!(super::get_calibration_value_v1("two1nine"), Some(11));
+        assert_eq!(super::get_calibration_value_v1("abcone"), None);
+    }
+
+    #[test]
+    fn get_calibration_value_v2_test() {
+        assert_eq!(super::get_calibration_value_v2("1abc2"), Some(12));
+        assert_eq!(super::get_calibration_value_v2("pqr3stu8vwx"), Some(38));
+        assert_eq!(super::get_calibration_value_v2("a1b2c3d4e5f"), Some(15));
+        assert_eq!(super::get_calibration_value_v2("treb7uchet"), Some(77));
+        assert_eq!(super::get_calibration_value_v2("1abc2three"), Some(13));
+        assert_eq!(super::get_calibration_value_v2("poneqr3stu8vwx"), Some(18));
+        assert_eq!(super::get_calibration_value_v2("one"), Some(11));
+        assert_eq!(super::get_calibration_value_v2("onetwothree"), Some(13));
+        assert_eq!(super::get_calibration_value_v2("9onetwothree9"), Some(99));
+        assert_eq!(
+            super::get_calibration_value_v2("qgrgqjlszpcnpq82"),
+            Some(82)
+        );
+    }
+}
@@ -0,0 +1,159 @@
+use anyhow::{anyhow, Result};
+use aoc_core::Solver;
+use std::cmp::max;
+
+/// Numbers of red, green and blue cubes in the bag.
+pub const MAX_CUBES: [u8; 3] = [12, 13, 14];
+
+pub fn parse_game_id(game_name: &str) -> Result<u32> {
+    let game_id = game_name
+        .strip_prefix("Game ")
+        .ok_or(anyhow!("Bad game name: {}", game_name))?;
+
+    Ok(game_id.parse()?)
+}
+
+pub fn parse_game_turn(turn: &str) -> Result<[u8; 3]> {
+    turn.split(',')
+        .map(|s| s.trim())
+        .try_fold([0, 0, 0], |acc, cubes_desc| {
+            let [r, g, b] = acc;
+
+            let [amount, color] = cubes_desc.split(' ').collect::<Vec<&str>>()[..] else {
+                return Err(anyhow!("Bad game turn line: {}", turn));
+            };
+
+            Ok(match color {
+                "red" => [amount.parse()?, g, b],
+                "green" => [r, amount.parse()?, b],
+                "blue" => [r, g, amount.parse()?],
+                _ => Err(anyhow!("Bad cubes color {} in turn line '{}'", color, turn))?,
+            })
+        })
+}
+
+pub fn parse_game_turns(content: &str) -> Result<Vec<[u8; 3]>> {
+    content
+        .split(';')
+        .map(|s| s.trim())
+        .map(parse_game_turn)
+        .collect()
+}
+
+pub fn parse_game(desc: &str) -> Result<(u32, Vec<[u8; 3]>)> {
+    let [name, content] = desc.split(':').collect::<Vec<&str>>()[..] else {
+        return Err(anyhow!("Bad game line: {}", desc));
+    };
+
+    Ok((parse_game_id(name)?, parse_game_turns(content)?))
+}
+
+pub fn find_min_cubes_set(game: &[[u8; 3]]) -> [u8; 3] {
+    game.iter()
+        .fold([0, 0, 0], |[accr, accg, accb], [r, g, b]| {
+            [max(accr, *r), max(accg, *g), max(accb, *b)]
+        })
+}
+
+pub fn calc_set_power(cubes_set: &[u8; 3]) -> u32 {
+    cubes_set.iter().map(|&n| u32::from(n)).product()
+}
+
+pub fn is_possible_game(game: &[[u8; 3]], max_cubes: &[u8; 3]) -> bool {
+    game.iter()
+        .all(|turn| turn.iter().zip(max_cubes).all(|(n, max)| n <= max))
+}
+
+pub struct Day2;
+
+impl Solver for Day2 {
+    const DAY: u8 = 2;
+
+    type Input = Vec<(u32, Vec<[u8; 3]>)>;
+    type Answer1 = u32;
+    type Answer2 = u32;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        input.lines().map(parse_game).collect()
+    }
+
+    fn part1(games: &Self::Input) -> Result<Self::Answer1> {
+        Ok(games
+            .iter()
+            .filter(|(_id, turns)| is_possible_game(turns, &MAX_CUBES))
+            .map(|(id, _turns)| id)
+            .sum())
+    }
+
+    fn part2(games: &Self::Input) -> Result<Self::Answer2> {
+        Ok(games
+            .iter()
+            .map(|(_id, turns)| calc_set_power(&find_min_cubes_set(turns)))
+            .sum())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn parse_game_test() {
+        assert_eq!(
+            super::parse_game("Game 1: 1 red").unwrap(),
+            (1, vec![[1, 0, 0]])
+        );
+    }
+
+    #[test]
+    fn parse_game_id_test() {
+        assert_eq!(super::parse_game_id("Game 11").unwrap(), 11);
+    }
+
+    #[test]
+    fn parse_game_turns_test() {
+        assert_eq!(
+            super::parse_game_turns("1 green; 2 red; 3 blue").unwrap(),
+            vec![[0, 1, 0], [2, 0, 0], [0, 0, 3]]
+        );
+    }
+
+    #[test]
+    fn parse_game_turn_ok_test() {
+        assert_eq!(
+            super::parse_game_turn("1 red, 2 green, 3 blue").unwrap(),
+            [1, 2, 3]
+        );
+        assert_eq!(
+            super::parse_game_turn("2 green, 3 blue").unwrap(),
+            [0, 2, 3]
+        );
+        assert_eq!(super::parse_game_turn("1 red, 3 blue").unwrap(), [1, 0, 3]);
+        assert_eq!(super::parse_game_turn("1 red, 2 green").unwrap(), [1, 2, 0]);
+    }
+
+    #[test]
+    fn parse_game_turn_bad_format_test() {
+        assert!(super::parse_game_turn("ai karramba lol,").is_err());
+    }
+
+    #[test]
+    fn parse_game_turn_bad_color_test() {
+        assert!(super::parse_game_turn("1 violet, 2 magenta, 3 purple").is_err());
+    }
+
+    #[test]
+    fn find_min_cubes_set_test() {
+        assert_eq!(
+            super::find_min_cubes_set(&[[4, 0, 3], [1, 2, 6], [0, 2, 0]]),
+            [4, 2, 6]
+        );
+        assert_eq!(
+            super::find_min_cubes_set(&[[20, 8, 6], [4, 13, 5], [1, 5, 0]]),
+            [20, 13, 6]
+        );
+    }
+
+    #[test]
+    fn calc_set_power_test() {
+        assert_eq!(super::calc_set_power(&[20, 13, 6]), 1560);
+    }
+}
@@ -0,0 +1,205 @@
+use anyhow::{anyhow, Result};
+use aoc_core::Solver;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Cards by their ids, with winning and available numbers.
+pub type Deck = BTreeMap<u32, (HashSet<u32>, HashSet<u32>)>;
+
+pub fn parse_card(line: &str) -> Result<(u32, HashSet<u32>, HashSet<u32>)> {
+    let [id, content] = line.split(':').collect::<Vec<&str>>()[..] else {
+        return Err(anyhow!(
+            "Wrong card description, can't parse card id and content: {}",
+            line
+        ));
+    };
+    let [_, card_id_str] = id.split_whitespace().collect::<Vec<&str>>()[..] else {
+        return Err(anyhow!(
+            "Wrong card description, can't parse card id: {}",
+            line
+        ));
+    };
+    let card_id = card_id_str.trim().parse::<u32>()?;
+    let [winning, available] = content.split('|').collect::<Vec<&str>>()[..] else {
+        return Err(anyhow!(
+            "Wrong card description, can't figure winning numbers from available: {}",
+            line
+        ));
+    };
+
+    let winning_nums = winning
+        .split_whitespace()
+        .map(|s| s.trim().parse::<u32>())
+        .collect::<Result<HashSet<u32>, _>>()?;
+    let available_nums = available
+        .split_whitespace()
+        .map(|s| s.trim().parse::<u32>())
+        .collect::<Result<HashSet<u32>, _>>()?;
+
+    Ok((card_id, winning_nums, available_nums))
+}
+
+pub fn calc_card_score(winning: &HashSet<u32>, available: &HashSet<u32>) -> u32 {
+    let matching_nums_count = available.intersection(winning).count();
+
+    if matching_nums_count > 0 {
+        2_u32.pow(matching_nums_count as u32 - 1)
+    } else {
+        0
+    }
+}
+
+pub fn load_deck_from_str(string: &str) -> Result<Deck> {
+    let mut result = BTreeMap::new();
+    for card_desc in string.lines() {
+        let (id, w, a) = parse_card(card_desc)?;
+
+        result.insert(id, (w, a));
+    }
+
+    Ok(result)
+}
+
+pub fn calc_total_score(deck: &Deck) -> u32 {
+    deck.values().map(|(w, a)| calc_card_score(w, a)).sum()
+}
+
+/// Counts original cards along with all copies they win.
+///
+/// Copies of cards past the end of the deck are not won.
+pub fn calc_total_cards(deck: &Deck) -> u32 {
+    let mut collected_cards = HashMap::new();
+    let mut calc_queue = Vec::from_iter(deck.keys().copied());
+
+    while let Some(card) = calc_queue.pop() {
+        match collected_cards.get_mut(&card) {
+            Some(count) => {
+                *count += 1;
+            }
+            None => {
+                collected_cards.insert(card, 1);
+            }
+        }
+
+        let (w, a) = &deck[&card];
+        let score = a.intersection(w).count();
+
+        calc_queue.extend(
+            deck.range((card + 1)..(card + 1 + score as u32))
+                .map(|(&id, _)| id),
+        );
+    }
+
+    collected_cards.values().sum()
+}
+
+pub struct Day4;
+
+impl Solver for Day4 {
+    const DAY: u8 = 4;
+
+    type Input = Deck;
+    type Answer1 = u32;
+    type Answer2 = u32;
+
+    fn parse(input: &str) -> Result<Self::Input> {
+        load_deck_from_str(input)
+    }
+
+    fn part1(deck: &Self::Input) -> Result<Self::Answer1> {
+        Ok(calc_total_score(deck))
+    }
+
+    fn part2(deck: &Self::Input) -> Result<Self::Answer2> {
+        Ok(calc_total_cards(deck))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::{BTreeMap, HashSet};
+
+    #[test]
+    fn parse_card_test() {
+        assert_eq!(
+            super::parse_card("Card   1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53").unwrap(),
+            (
+                1,
+                HashSet::from([41, 48, 83, 86, 17]),
+                HashSet::from([83, 86, 6, 31, 17, 9, 48, 53])
+            )
+        );
+    }
+
+    #[test]
+    fn calc_card_score() {
+        assert_eq!(
+            super::calc_card_score(&HashSet::from([1, 2, 3]), &HashSet::from([4, 5, 6])),
+            0
+        );
+        assert_eq!(
+            super::calc_card_score(&HashSet::from([1, 2, 3]), &HashSet::from([1, 5, 6])),
+            1
+        );
+        assert_eq!(
+            super::calc_card_score(&HashSet::from([1, 2, 3]), &HashSet::from([1, 2, 6])),
+            2
+        );
+        assert_eq!(
+            super::calc_card_score(&HashSet::from([1, 2, 3]), &HashSet::from([1, 2, 3])),
+            4
+        );
+        assert_eq!(
+            super::calc_card_score(&HashSet::from([1, 2, 3, 4]), &HashSet::from([1, 2, 3, 4])),
+            8
+        );
+    }
+
+    #[test]
+    fn load_deck_from_str_test() {
+        let cards = "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19";
+
+        assert_eq!(
+            super::load_deck_from_str(cards).unwrap(),
+            BTreeMap::from([
+                (
+                    1,
+                    (
+                        HashSet::from([41, 48, 83, 86, 17]),
+                        HashSet::from([83, 86, 6, 31, 17, 9, 48, 53])
+                    )
+                ),
+                (
+                    2,
+                    (
+                        HashSet::from([13, 32, 20, 16, 61]),
+                        HashSet::from([61, 30, 68, 82, 17, 32, 24, 19])
+                    )
+                )
+            ])
+        );
+    }
+
+    #[test]
+    fn calc_total_cards() {
+        let cards = "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19
+Card 3:  1 21 53 59 44 | 69 82 63 72 16 21 14  1
+Card 4: 41 92 73 84 69 | 59 84 76 51 58  5 54 83
+Card 5: 87 83 26 28 32 | 88 30 70 12 93 22 82 36
+Card 6: 31 18 13 56 72 | 74 77 10 23 35 67 36 11";
+
+        assert_eq!(
+            super::calc_total_cards(&super::load_deck_from_str(cards).unwrap()),
+            30
+        );
+
+        let cards = "Card 1: 41 48 83 86 17 | 83 86  6 31 17  9 48 53
+Card 2: 13 32 20 16 61 | 61 30 68 82 17 32 24 19";
+
+        assert_eq!(
+            super::calc_total_cards(&super::load_deck_from_str(cards).unwrap()),
+            3
+        );
+    }
+}
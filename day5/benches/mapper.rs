@@ -0,0 +1,77 @@
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use day5::{Id, Mapper};
+use std::hint::black_box;
+
+/// Lookup the way `Mapper::map` used to do it: scanning every range until the first one containing the id.
+fn linear_scan_map(mappings: &[(Id, Id, Id)], src_id: Id) -> Id {
+    for &(dst_start, src_start, len) in mappings {
+        if src_start <= src_id && src_id - src_start < len {
+            return dst_start + (src_id - src_start);
+        }
+    }
+
+    src_id
+}
+
+/// Generates `count` adjacent ranges shuffled around with a fixed linear congruential generator, so runs compare.
+fn generate_ranges(count: u64) -> Vec<(Id, Id, Id)> {
+    let mut state = 0x2545_f491_4f6c_dd1d_u64;
+    let mut next = move || {
+        state = state
+            .wrapping_mul(6_364_136_223_846_793_005)
+            .wrapping_add(1_442_695_040_888_963_407);
+        (state >> 33) as Id
+    };
+
+    let mut src_start = 0;
+    (0..count)
+        .map(|_| {
+            let len = 1 + next() % 1_000_000;
+            let range = (next() % 1_000_000_000_000, src_start, len);
+            src_start += len + next() % 1_000;
+            range
+        })
+        .collect()
+}
+
+fn bench_map(c: &mut Criterion) {
+    let mut group = c.benchmark_group("map");
+
+    for count in [100, 1_000, 10_000, 50_000] {
+        let ranges = generate_ranges(count);
+        let mapper = Mapper::new("seed", "soil", ranges.clone()).unwrap();
+        let domain_end = ranges.iter().map(|&(_, src, len)| src + len).max().unwrap();
+        let queries: Vec<Id> = (0..1_000).map(|i| i * (domain_end / 1_000)).collect();
+
+        group.throughput(Throughput::Elements(queries.len() as u64));
+        group.bench_with_input(
+            BenchmarkId::new("binary_search", count),
+            &queries,
+            |b, queries| {
+                b.iter(|| {
+                    queries
+                        .iter()
+                        .map(|&q| mapper.map(black_box(q)))
+                        .sum::<Id>()
+                })
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("linear_scan", count),
+            &queries,
+            |b, queries| {
+                b.iter(|| {
+                    queries
+                        .iter()
+                        .map(|&q| linear_scan_map(&ranges, black_box(q)))
+                        .sum::<Id>()
+                })
+            },
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_map);
+criterion_main!(benches);
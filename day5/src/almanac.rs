@@ -1,127 +1,470 @@
-use crate::mapper::Mapper;
+use crate::graph::{CategoryGraph, PathError};
+use crate::mapper::{checked_range, merge_ranges, Id, Mapper};
+use crate::parser::{AlmanacParseError, AlmanacReader};
+use crate::validation::{self, Issue, MapDefinition, RangeDefinition};
 use anyhow::{anyhow, Result};
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Display;
+use std::io::BufRead;
 use std::ops::Range;
+use std::sync::OnceLock;
 
+/// Part of traced ids going through a map.
+#[derive(Debug, PartialEq)]
+pub struct TracedPiece {
+    pub src: Range<Id>,
+    pub dst: Range<Id>,
+    /// Range of the map moving the piece as written in the almanac, or `None` when no range covers the piece.
+    pub matched: Option<RangeDefinition>,
+}
+
+/// Step of traced ids through one of the maps, see `Almanac::trace`.
+#[derive(Debug, PartialEq)]
+pub struct Hop {
+    pub source: String,
+    pub destination: String,
+    pub pieces: Vec<TracedPiece>,
+}
+
+impl Display for Hop {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fn ids(range: &Range<Id>) -> String {
+            if range.end - range.start == 1 {
+                range.start.to_string()
+            } else {
+                format!("{:?}", range)
+            }
+        }
+
+        write!(f, "{}-to-{} map:", self.source, self.destination)?;
+        for piece in self.pieces.iter() {
+            write!(
+                f,
+                "\n  {} {} -> {} {} via ",
+                self.source,
+                ids(&piece.src),
+                self.destination,
+                ids(&piece.dst)
+            )?;
+            match &piece.matched {
+                Some(range) => {
+                    write!(
+                        f,
+                        "range {} {} {}",
+                        range.dst_start, range.src_start, range.len
+                    )?;
+                    if let Some(line) = range.line {
+                        write!(f, " at line {}", line)?;
+                    }
+                }
+                None => write!(f, "identity")?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// How the numbers on the `seeds:` line are read.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SeedMode {
+    /// Every number is a seed on its own.
+    Individual,
+    /// Numbers go in pairs of range start and range length.
+    Ranges,
+}
+
+impl Display for SeedMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SeedMode::Individual => write!(f, "individual seeds"),
+            SeedMode::Ranges => write!(f, "seed ranges"),
+        }
+    }
+}
+
+/// Seeds along with the maps between categories.
+///
+/// Maps from seeds to every category reachable by a single chain of maps are composed up front, other composed maps
+/// are cached on first use in a slot set aside for every pair of categories, so queries only need `&self`. The maps
+/// themselves never change once the almanac is built, so cached maps stay valid.
 #[derive(Debug)]
 pub struct Almanac {
-    pub seeds: Vec<Range<u64>>,
+    pub seeds: Vec<Id>,
     maps: Vec<Mapper>,
+    graph: CategoryGraph,
+    direct_maps: HashMap<(String, String), OnceLock<Mapper>>,
+    definitions: Vec<MapDefinition>,
 }
 
 impl Almanac {
-    pub fn new(seeds: Vec<Range<u64>>, maps: Vec<Mapper>) -> Self {
-        // build direct maps from seeds to everything
+    /// Builds an almanac from maps built in code, validation then reports issues without line numbers.
+    ///
+    /// `Mapper::new` already resolves overlapping source ranges, the first defined one winning, so validation cannot
+    /// report such overlaps here. Parse the almanac text instead to have them reported.
+    pub fn new(seeds: Vec<Id>, maps: Vec<Mapper>) -> Self {
+        let definitions = maps.iter().map(MapDefinition::of).collect();
 
-        Almanac { seeds, maps }
+        Self::with_definitions(seeds, maps, definitions)
     }
 
-    fn build_map(maps: &Vec<Mapper>, what: &str, to_what: &str) -> Option<Mapper> {
-        let map_target = to_what;
-        let mut curr_mapped_item = what;
+    /// Parses the almanac line by line, see `AlmanacReader` for the accepted format.
+    pub fn from_reader(reader: impl BufRead) -> Result<Self, AlmanacParseError> {
+        let mut almanac_reader = AlmanacReader::new(reader)?;
+        let (maps, definitions) = almanac_reader
+            .by_ref()
+            .collect::<Result<Vec<(Mapper, MapDefinition)>, AlmanacParseError>>()?
+            .into_iter()
+            .unzip();
 
-        while curr_mapped_item != map_target {
-            if let Some(idx) = maps.iter().position(|m| m.source == curr_mapped_item) {
-            } else {
-                return None;
+        Ok(Self::with_definitions(
+            almanac_reader.seeds().to_vec(),
+            maps,
+            definitions,
+        ))
+    }
+
+    fn with_definitions(
+        seeds: Vec<Id>,
+        maps: Vec<Mapper>,
+        definitions: Vec<MapDefinition>,
+    ) -> Self {
+        let graph = CategoryGraph::new(&maps);
+
+        let mut direct_maps = HashMap::new();
+        for what in graph.categories() {
+            for to_what in graph.categories() {
+                // build direct maps from seeds to everything
+                let direct_map = match what {
+                    "seed" => Self::build_map(&maps, &graph, what, to_what).ok(),
+                    _ => None,
+                };
+                direct_maps.insert(
+                    (what.to_string(), to_what.to_string()),
+                    direct_map.map_or_else(OnceLock::new, OnceLock::from),
+                );
             }
         }
 
-        None
+        Almanac {
+            seeds,
+            maps,
+            graph,
+            direct_maps,
+            definitions,
+        }
     }
 
-    pub fn try_map(&mut self, what: &str, what_id: u64, to_what: &str) -> Option<u64> {
-        let map_target = to_what;
-        let mut curr_mapped_item = what;
-        let mut curr_mapped_value = what_id;
+    /// Checks the almanac for problems that make it compute wrong results, most severe first.
+    pub fn validate(&self) -> Vec<Issue> {
+        let mut issues = validation::validate(&self.definitions);
+        issues.sort_by_key(|issue| Reverse(issue.severity));
 
-        while curr_mapped_item != map_target {
-            if let Some(idx) = self.maps.iter().position(|m| m.source == curr_mapped_item) {
-                curr_mapped_value = self.maps[idx].map(curr_mapped_value);
-                curr_mapped_item = &self.maps[idx].destination;
-            } else {
-                return None;
+        issues
+    }
+
+    pub fn maps(&self) -> &[Mapper] {
+        &self.maps
+    }
+
+    /// Interprets the numbers on the `seeds:` line as ranges of seed ids.
+    pub fn seed_ranges(&self, mode: SeedMode) -> Result<Vec<Range<Id>>> {
+        match mode {
+            SeedMode::Individual => Ok(self
+                .seeds
+                .iter()
+                .map(|&seed| checked_range(seed, 1))
+                .collect::<Result<_, _>>()?),
+            SeedMode::Ranges => {
+                let pairs = self.seeds.chunks_exact(2);
+                if !pairs.remainder().is_empty() {
+                    Err(anyhow!(
+                        "Seed ranges need pairs of start and length, but there are {} numbers",
+                        self.seeds.len()
+                    ))?
+                }
+
+                Ok(pairs
+                    .map(|pair| checked_range(pair[0], pair[1]))
+                    .collect::<Result<_, _>>()?)
             }
         }
-
-        Some(curr_mapped_value)
     }
-}
 
-impl TryFrom<String> for Almanac {
-    type Error = anyhow::Error;
+    /// Finds the seed among `seed_ranges` planted closest, as `(seed, location)`.
+    ///
+    /// Over a range of seeds, the closest location is at the range start or where some map along the chain starts
+    /// shifting ids differently, so only seeds mapped onto breakpoints of the maps are checked. Returns `None` if
+    /// there are no seeds or no single chain of maps from seeds to locations.
+    pub fn min_location(&self, seed_ranges: &[Range<Id>]) -> Option<(Id, Id)> {
+        let path = self.graph.path("seed", "location").ok()?;
 
-    fn try_from(value: String) -> Result<Self> {
-        let [seeds_def, map_defs @ ..] = &value.split("\n\n").collect::<Vec<&str>>()[..] else {
-            Err(anyhow!("Invalid structure of almanac definition: at least seeds definition is required: {}", value))?
-        };
+        // project breakpoints back from the last map to seeds
+        let mut breakpoints = BTreeSet::new();
+        for &idx in path.iter().rev() {
+            breakpoints = self.maps[idx].preimage(&breakpoints);
+            breakpoints.extend(self.maps[idx].src_breakpoints());
+        }
 
-        let seeds = (*seeds_def)
-            .trim()
-            .strip_prefix("seeds: ")
-            .ok_or(anyhow!("Invalid seeds definition: {}", *seeds_def))
-            .and_then(|seed_nums_def| {
-                seed_nums_def
-                    .split_whitespace()
-                    .map(|s| {
-                        s.parse::<u64>()
-                            .map_err(|e| anyhow!("Failed to parse seed: {}", e))
-                    })
-                    .collect::<Result<Vec<u64>>>()
-            })?
-            .windows(2)
-            .map(|w| {
-                if let &[start, length] = w {
-                    Ok(start..(start + length))
-                } else {
-                    Err(anyhow!("Invalid seed ranges definition."))
-                }
+        merge_ranges(seed_ranges.to_vec())
+            .into_iter()
+            .flat_map(|r| [r.start].into_iter().chain(breakpoints.range(r).copied()))
+            .map(|seed| {
+                let location = path.iter().fold(seed, |id, &idx| self.maps[idx].map(id));
+                (seed, location)
             })
-            .collect::<Result<Vec<Range<u64>>>>()?;
-
-        let mut maps: Vec<Mapper> = vec![];
-        for map_def in map_defs {
-            let [name_def, range_defs @ ..] = &(map_def.lines().collect::<Vec<&str>>())[..] else {
-                Err(anyhow!("Invalid structure of the map definition: name and at least one range map is required: {}", map_def))?
-            };
-
-            let (src, dest) = name_def
-                .trim()
-                .strip_suffix(" map:")
-                .ok_or(anyhow!("Invalid map definition: {}", name_def))
-                .and_then(|name| {
-                    let name_parts: Vec<&str> = name.split("-").collect();
-                    match &(name_parts)[..] {
-                        [s, _, d] => Ok((*s, *d)),
-                        _ => Err(anyhow!("Invalid map name: {}", name)),
+            .min_by_key(|&(_seed, location)| location)
+    }
+
+    /// Follows `what_ids` through every map on the way to `to_what`, showing how ids split between map ranges.
+    ///
+    /// Pieces point at the ranges as written in the almanac, even where the map merges adjacent ranges or cuts
+    /// overlapping ones.
+    pub fn trace(
+        &self,
+        what: &str,
+        what_ids: Range<Id>,
+        to_what: &str,
+    ) -> Result<Vec<Hop>, PathError> {
+        let mut ranges = vec![what_ids];
+        let mut hops = vec![];
+        for idx in self.graph.path(what, to_what)? {
+            let map = &self.maps[idx];
+            let definitions: Vec<(Range<Id>, &RangeDefinition)> = self.definitions[idx]
+                .ranges
+                .iter()
+                .filter(|r| r.dst().is_some())
+                .filter_map(|r| r.src().map(|src| (src, r)))
+                .collect();
+
+            let mut pieces: Vec<TracedPiece> = vec![];
+            for (src, dst) in ranges.iter().flat_map(|r| map.split(r.clone())) {
+                // the map merges adjacent ranges moving ids alike, cut pieces back where written ranges start and end
+                let mut cuts: Vec<Id> = definitions
+                    .iter()
+                    .flat_map(|(def_src, _)| [def_src.start, def_src.end])
+                    .filter(|&cut| src.start < cut && cut < src.end)
+                    .chain([src.end])
+                    .collect();
+                cuts.sort();
+                cuts.dedup();
+
+                let mut start = src.start;
+                for end in cuts {
+                    // the first range defined wins where ranges overlap
+                    let matched = definitions
+                        .iter()
+                        .find(|(def_src, _)| def_src.contains(&start))
+                        .map(|&(_, r)| r.clone());
+                    let piece_dst = dst.start + (start - src.start)..dst.start + (end - src.start);
+                    match pieces.last_mut() {
+                        Some(last) if start != src.start && last.matched == matched => {
+                            last.src.end = end;
+                            last.dst.end = piece_dst.end;
+                        }
+                        _ => pieces.push(TracedPiece {
+                            src: start..end,
+                            dst: piece_dst,
+                            matched,
+                        }),
                     }
-                })?;
-
-            let mut range_maps: Vec<(u64, u64, u64)> = vec![];
-            for range_def in range_defs {
-                let [dst_start, src_start, length] = range_def
-                    .split_whitespace()
-                    .map(|el| {
-                        el.parse::<u64>()
-                            .map_err(|e| anyhow!("Failed to parse range definition element: {}", e))
-                    })
-                    .collect::<Result<Vec<u64>>>()?[..]
-                else {
-                    Err(anyhow!("Failed to parse range definition: {}", range_def))?
-                };
+                    start = end;
+                }
+            }
+
+            ranges = pieces.iter().map(|piece| piece.dst.clone()).collect();
+            hops.push(Hop {
+                source: map.source.clone(),
+                destination: map.destination.clone(),
+                pieces,
+            });
+        }
+
+        Ok(hops)
+    }
+
+    /// Lists every chain of categories leading from `what` to `to_what`.
+    pub fn paths(&self, what: &str, to_what: &str) -> Result<Vec<Vec<String>>, PathError> {
+        Ok(self
+            .graph
+            .paths(what, to_what)?
+            .iter()
+            .map(|path| self.graph.categories_of(what, path))
+            .collect())
+    }
+
+    /// Lists maps along the single chain leading from `what` to `to_what`, in the order ids go through them.
+    pub fn chain(&self, what: &str, to_what: &str) -> Result<Vec<&Mapper>, PathError> {
+        Ok(self
+            .graph
+            .path(what, to_what)?
+            .into_iter()
+            .map(|idx| &self.maps[idx])
+            .collect())
+    }
+
+    /// Finds the single chain of categories leading from `what` to `to_what`.
+    pub fn path(&self, what: &str, to_what: &str) -> Result<Vec<String>, PathError> {
+        let path = self.graph.path(what, to_what)?;
+
+        Ok(self.graph.categories_of(what, &path))
+    }
+
+    /// Composes the chain of maps leading from `what` to `to_what` into a single direct map.
+    fn build_map(
+        maps: &[Mapper],
+        graph: &CategoryGraph,
+        what: &str,
+        to_what: &str,
+    ) -> Result<Mapper> {
+        let mut result = Mapper::identity(what, what);
+
+        for idx in graph.path(what, to_what)? {
+            result = (&result + &maps[idx])
+                .ok_or_else(|| anyhow!("Cannot compose {} with {}", result, maps[idx]))?;
+        }
+
+        Ok(result)
+    }
 
-                range_maps.push((dst_start, src_start, length));
+    /// Returns cached direct map from `what` to `to_what`, building it on first use.
+    ///
+    /// When there is no chain of maps from `what` to `to_what`, the chain going the other way is composed and inverted.
+    fn direct_map(&self, what: &str, to_what: &str) -> Result<&Mapper> {
+        fn is_unreachable(err: &anyhow::Error) -> bool {
+            matches!(
+                err.downcast_ref::<PathError>(),
+                Some(PathError::Unreachable { .. })
+            )
+        }
+
+        let key = (what.to_string(), to_what.to_string());
+        if let Some(direct_map) = self.direct_maps.get(&key).and_then(OnceLock::get) {
+            return Ok(direct_map);
+        }
+
+        let direct_map = match Self::build_map(&self.maps, &self.graph, what, to_what) {
+            Err(err) if is_unreachable(&err) => {
+                Self::build_map(&self.maps, &self.graph, to_what, what)
+                    .map_err(|reverse_err| {
+                        if is_unreachable(&reverse_err) {
+                            err
+                        } else {
+                            reverse_err
+                        }
+                    })?
+                    .inverse()?
             }
+            direct_map => direct_map?,
+        };
+
+        Ok(self
+            .direct_maps
+            .get(&key)
+            .expect("maps are only built between categories of the almanac")
+            .get_or_init(|| direct_map))
+    }
+
+    /// Returns the single map from `what` to `to_what` ids, composing the chain of maps between them.
+    pub fn composed_map(&self, what: &str, to_what: &str) -> Result<&Mapper> {
+        self.direct_map(what, to_what)
+    }
+
+    /// Maps a single `what` id to a `to_what` id.
+    pub fn try_map(&self, what: &str, what_id: Id, to_what: &str) -> Result<Id> {
+        Ok(self.direct_map(what, to_what)?.map(what_id))
+    }
+
+    /// Maps whole ranges of `what` ids to `to_what` ids, splitting them at the mapping boundaries of each map
+    /// along the way. Resulting ranges are sorted and merged.
+    pub fn try_map_ranges(
+        &self,
+        what: &str,
+        what_ranges: &[Range<Id>],
+        to_what: &str,
+    ) -> Result<Vec<Range<Id>>> {
+        Ok(self
+            .direct_map(what, to_what)?
+            .map_ranges(&merge_ranges(what_ranges.to_vec())))
+    }
+}
+
+impl PartialEq for Almanac {
+    fn eq(&self, other: &Self) -> bool {
+        self.seeds == other.seeds && self.maps == other.maps
+    }
+}
+
+/// Writes the almanac in the same format it is parsed from.
+impl Display for Almanac {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "seeds:")?;
+        for seed in self.seeds.iter() {
+            write!(f, " {}", seed)?;
+        }
+        writeln!(f)?;
 
-            maps.push(Mapper::new(src, dest, range_maps));
+        for map in self.maps.iter() {
+            write!(f, "\n{}:\n", map)?;
+            for (dst_start, src_start, len) in map.ranges() {
+                writeln!(f, "{} {} {}", dst_start, src_start, len)?;
+            }
         }
 
-        Ok(Almanac { seeds, maps })
+        Ok(())
+    }
+}
+
+impl TryFrom<String> for Almanac {
+    type Error = AlmanacParseError;
+
+    fn try_from(value: String) -> Result<Self, AlmanacParseError> {
+        Almanac::from_reader(value.as_bytes())
     }
 }
 
 #[cfg(test)]
 mod test {
-    use crate::almanac::Almanac;
+    use crate::almanac::{Almanac, SeedMode};
+    use crate::graph::{CategoryGraph, PathError};
+    use crate::mapper::{merge_ranges, Id, Mapper};
+    use crate::parser::AlmanacParseError;
+    use crate::validation::{Issue, IssueKind, Severity};
+
+    const EXAMPLE: &str = "seeds: 79 14 55 13
+
+seed-to-soil map:
+50 98 2
+52 50 48
+
+soil-to-fertilizer map:
+0 15 37
+37 52 2
+39 0 15
+
+fertilizer-to-water map:
+49 53 8
+0 11 42
+42 0 7
+57 7 4
+
+water-to-light map:
+88 18 7
+18 25 70
+
+light-to-temperature map:
+45 77 23
+81 45 19
+68 64 13
+
+temperature-to-humidity map:
+0 69 1
+1 0 69
+
+humidity-to-location map:
+60 56 37
+56 93 4";
 
     #[test]
     fn almanac_tryfrom_happy_test() {
@@ -134,14 +477,628 @@ mod test {
         );
         let almanac = Almanac::try_from(almanac_def);
         println!("Almanac: {:?}", almanac);
-        assert!(matches!(almanac, Ok(_)));
+        assert!(almanac.is_ok());
     }
 
     #[test]
-    fn almanac_build_map_test() {
+    fn almanac_tryfrom_errors_test() {
+        for (almanac_def, expected) in [
+            (
+                "",
+                AlmanacParseError::MissingSeeds {
+                    line: 1,
+                    column: 1,
+                    token: "".to_string(),
+                },
+            ),
+            (
+                "  seedz: 79 14",
+                AlmanacParseError::MissingSeeds {
+                    line: 1,
+                    column: 3,
+                    token: "seedz:".to_string(),
+                },
+            ),
+            (
+                "seeds: 79\n  14 x55",
+                AlmanacParseError::InvalidNumber {
+                    line: 2,
+                    column: 6,
+                    token: "x55".to_string(),
+                },
+            ),
+            (
+                "seeds: 79 14\n\nseed-to-soil map:\n50 98 2\n\nsoil to water map:\n0 15 37",
+                AlmanacParseError::InvalidMapName {
+                    line: 6,
+                    column: 1,
+                    token: "soil to water map:".to_string(),
+                },
+            ),
+            (
+                "seeds: 79 14\n\nseed-to-soil map:\n50 98 2\nsoil-to-fertiliser mpa:\n0 15 37",
+                AlmanacParseError::InvalidMapName {
+                    line: 5,
+                    column: 1,
+                    token: "soil-to-fertiliser mpa:".to_string(),
+                },
+            ),
+            (
+                "seeds: 79 14\n\nseed-to-soil map:\n50 98 2\n52 50",
+                AlmanacParseError::InvalidRange {
+                    line: 5,
+                    column: 6,
+                    token: "".to_string(),
+                },
+            ),
+            (
+                "seeds: 79 14\n\nseed-to-soil map:\n50 98 2 2",
+                AlmanacParseError::InvalidRange {
+                    line: 4,
+                    column: 9,
+                    token: "2".to_string(),
+                },
+            ),
+            (
+                "seeds: 79 14\n\nseed-to-soil map:\n50 -98 2",
+                AlmanacParseError::InvalidNumber {
+                    line: 4,
+                    column: 4,
+                    token: "-98".to_string(),
+                },
+            ),
+        ] {
+            assert_eq!(
+                Almanac::try_from(almanac_def.to_string()),
+                Err(expected),
+                "{:?}",
+                almanac_def
+            );
+        }
+    }
+
+    #[test]
+    fn almanac_seed_ranges_test() {
+        let almanac = Almanac::try_from(EXAMPLE.to_string()).unwrap();
+
+        assert_eq!(almanac.seeds, vec![79, 14, 55, 13]);
+        assert_eq!(
+            almanac.seed_ranges(SeedMode::Individual).unwrap(),
+            vec![79..80, 14..15, 55..56, 13..14]
+        );
+        assert_eq!(
+            almanac.seed_ranges(SeedMode::Ranges).unwrap(),
+            vec![79..93, 55..68]
+        );
+
+        let odd_almanac = Almanac::try_from(String::from("seeds: 79 14 55")).unwrap();
+        assert_eq!(
+            odd_almanac.seed_ranges(SeedMode::Individual).unwrap(),
+            vec![79..80, 14..15, 55..56]
+        );
+        assert!(odd_almanac.seed_ranges(SeedMode::Ranges).is_err());
+
+        let overflowing_almanac = Almanac::new(vec![Id::MAX - 1, 2], vec![]);
+        assert!(overflowing_almanac
+            .seed_ranges(SeedMode::Individual)
+            .is_ok());
+        assert!(overflowing_almanac.seed_ranges(SeedMode::Ranges).is_err());
+    }
+
+    #[test]
+    fn almanac_closest_location_test() {
+        let almanac = Almanac::try_from(EXAMPLE.to_string()).unwrap();
+
+        for (mode, closest_location) in [(SeedMode::Individual, 35), (SeedMode::Ranges, 46)] {
+            let seeds = almanac.seed_ranges(mode).unwrap();
+            let locations = almanac.try_map_ranges("seed", &seeds, "location").unwrap();
+
+            assert_eq!(locations.first().map(|r| r.start), Some(closest_location));
+        }
+    }
+
+    #[test]
+    fn almanac_min_location_test() {
+        let almanac = Almanac::try_from(EXAMPLE.to_string()).unwrap();
+
+        for (mode, min_location) in [
+            (SeedMode::Individual, (13, 35)),
+            (SeedMode::Ranges, (82, 46)),
+        ] {
+            let seeds = almanac.seed_ranges(mode).unwrap();
+
+            assert_eq!(almanac.min_location(&seeds), Some(min_location));
+        }
+        assert_eq!(almanac.min_location(&[]), None);
+    }
+
+    #[test]
+    fn almanac_try_map_test() {
+        let almanac = Almanac::try_from(EXAMPLE.to_string()).unwrap();
+
+        assert_eq!(almanac.try_map("seed", 79, "location").unwrap(), 82);
+        assert_eq!(almanac.try_map("seed", 14, "location").unwrap(), 43);
+        assert_eq!(almanac.try_map("seed", 55, "location").unwrap(), 86);
+        assert_eq!(almanac.try_map("seed", 13, "location").unwrap(), 35);
+        assert!(almanac.try_map("seed", 13, "nonexistent").is_err());
+    }
+
+    #[test]
+    #[allow(clippy::single_range_in_vec_init)]
+    fn almanac_trace_test() {
+        let almanac = Almanac::try_from(EXAMPLE.to_string()).unwrap();
+
+        let hops = almanac.trace("seed", 79..80, "location").unwrap();
+        assert_eq!(
+            hops.iter()
+                .map(|hop| hop
+                    .pieces
+                    .iter()
+                    .map(|p| (
+                        p.dst.start,
+                        p.matched
+                            .as_ref()
+                            .map(|r| (r.dst_start, r.src_start, r.len))
+                    ))
+                    .collect())
+                .collect::<Vec<Vec<(Id, Option<(Id, Id, Id)>)>>>(),
+            vec![
+                vec![(81, Some((52, 50, 48)))],
+                vec![(81, None)],
+                vec![(81, None)],
+                vec![(74, Some((18, 25, 70)))],
+                vec![(78, Some((68, 64, 13)))],
+                vec![(78, None)],
+                vec![(82, Some((60, 56, 37)))],
+            ]
+        );
+
+        let hops = almanac.trace("seed", 97..101, "location").unwrap();
+        assert_eq!(
+            hops[0].to_string(),
+            "seed-to-soil map:
+  seed 97 -> soil 99 via range 52 50 48 at line 5
+  seed 98..100 -> soil 50..52 via range 50 98 2 at line 4
+  seed 100 -> soil 100 via identity"
+        );
+        assert_eq!(
+            merge_ranges(
+                hops.last()
+                    .unwrap()
+                    .pieces
+                    .iter()
+                    .map(|p| p.dst.clone())
+                    .collect()
+            ),
+            almanac
+                .try_map_ranges("seed", &[97..101], "location")
+                .unwrap()
+        );
+
+        assert!(almanac.trace("location", 82..83, "seed").is_err());
+    }
+
+    #[test]
+    fn almanac_trace_definitions_test() {
+        let almanac = Almanac::try_from(String::from(
+            "seeds: 55 10
+
+seed-to-soil map:
+52 50 10
+62 60 10
+
+soil-to-location map:
+0 60 5
+100 55 20",
+        ))
+        .unwrap();
+
+        let hops = almanac.trace("seed", 55..65, "location").unwrap();
+        assert_eq!(
+            hops.iter()
+                .map(|hop| hop.to_string())
+                .collect::<Vec<String>>(),
+            vec![
+                "seed-to-soil map:
+  seed 55..60 -> soil 57..62 via range 52 50 10 at line 4
+  seed 60..65 -> soil 62..67 via range 62 60 10 at line 5",
+                "soil-to-location map:
+  soil 57..60 -> location 102..105 via range 100 55 20 at line 9
+  soil 60..62 -> location 0..2 via range 0 60 5 at line 8
+  soil 62..65 -> location 2..5 via range 0 60 5 at line 8
+  soil 65..67 -> location 110..112 via range 100 55 20 at line 9",
+            ]
+        );
+    }
+
+    #[test]
+    fn almanac_try_map_ranges_test() {
+        let almanac = Almanac::try_from(EXAMPLE.to_string()).unwrap();
+
+        let locations = almanac
+            .try_map_ranges("seed", &[79..93, 55..68], "location")
+            .unwrap();
+        assert_eq!(locations.first().map(|r| r.start), Some(46));
+        assert_eq!(locations.iter().map(|r| r.end - r.start).sum::<Id>(), 27);
+
+        assert_eq!(
+            almanac
+                .try_map_ranges("seed", &[79..80, 14..15], "location")
+                .unwrap(),
+            vec![43..44, 82..83]
+        );
+        assert!(almanac
+            .try_map_ranges("seed", &[79..93, 55..68], "nonexistent")
+            .is_err());
+    }
+
+    #[test]
+    fn almanac_reverse_try_map_test() {
+        let almanac = Almanac::try_from(EXAMPLE.to_string()).unwrap();
+
+        assert_eq!(almanac.try_map("location", 82, "seed").unwrap(), 79);
+        assert_eq!(almanac.try_map("location", 43, "seed").unwrap(), 14);
+        assert_eq!(almanac.try_map("location", 35, "soil").unwrap(), 13);
+
+        let seeds = almanac
+            .try_map_ranges("location", &[46..47, 82..83], "seed")
+            .unwrap();
+        assert_eq!(seeds, vec![79..80, 82..83]);
+        for seed in seeds.iter().flat_map(|r| r.clone()) {
+            let location = almanac.try_map("seed", seed, "location").unwrap();
+            assert!(location == 46 || location == 82);
+        }
+    }
+
+    #[test]
+    fn almanac_reverse_try_map_not_injective_test() {
+        let almanac = Almanac::try_from(String::from(
+            "seeds: 79 14
+
+seed-to-soil map:
+50 98 2",
+        ))
+        .unwrap();
+
+        assert_eq!(almanac.try_map("seed", 98, "soil").unwrap(), 50);
+        assert!(almanac.try_map("soil", 50, "seed").is_err());
+    }
+
+    #[test]
+    fn almanac_branching_categories_test() {
+        let almanac = Almanac::try_from(String::from(
+            "seeds: 79 14
+
+seed-to-soil map:
+50 98 2
+52 50 48
+
+seed-to-light map:
+10 70 20
+70 10 20
+
+soil-to-location map:
+0 50 10
+
+light-to-location map:
+100 0 50",
+        ))
+        .unwrap();
+
+        assert_eq!(almanac.try_map("seed", 75, "light").unwrap(), 15);
+        assert_eq!(almanac.try_map("seed", 52, "soil").unwrap(), 54);
+        assert_eq!(almanac.try_map("light", 15, "seed").unwrap(), 75);
+        assert_eq!(
+            almanac.paths("seed", "location").unwrap(),
+            vec![
+                vec!["seed", "soil", "location"],
+                vec!["seed", "light", "location"]
+            ]
+        );
+
+        assert_eq!(
+            almanac
+                .try_map("soil", 13, "light")
+                .unwrap_err()
+                .downcast_ref::<PathError>(),
+            Some(&PathError::Unreachable {
+                from: "soil".to_string(),
+                to: "light".to_string()
+            })
+        );
+
+        let err = almanac.try_map("seed", 75, "location").unwrap_err();
         assert!(matches!(
-            Almanac::build_map(&vec![], "nonexistent", "nonexistent"),
-            None
+            err.downcast_ref::<PathError>(),
+            Some(PathError::Ambiguous { paths, .. }) if paths.len() == 2
         ));
     }
+
+    #[test]
+    fn almanac_cyclic_categories_test() {
+        let almanac = Almanac::try_from(String::from(
+            "seeds: 79 14
+
+seed-to-soil map:
+50 98 2
+
+soil-to-water map:
+0 15 37
+
+water-to-soil map:
+15 0 37
+
+water-to-location map:
+60 56 37",
+        ))
+        .unwrap();
+
+        let err = almanac.try_map("seed", 79, "location").unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<PathError>(),
+            Some(&PathError::Cycle(vec![
+                "soil".to_string(),
+                "water".to_string(),
+                "soil".to_string()
+            ]))
+        );
+
+        let err = almanac.try_map("seed", 79, "light").unwrap_err();
+        assert_eq!(
+            err.downcast_ref::<PathError>(),
+            Some(&PathError::UnknownCategory("light".to_string()))
+        );
+    }
+
+    #[test]
+    fn almanac_validate_test() {
+        let almanac = Almanac::try_from(EXAMPLE.to_string()).unwrap();
+        assert_eq!(almanac.validate(), vec![]);
+
+        let almanac = Almanac::try_from(format!(
+            "seeds: 79 14
+
+seed-to-soil map:
+50 98 2
+52 50 0
+50 {} 2
+
+soil-to-water map:
+0 15 37
+
+seed-to-soil map:
+0 15 37
+10 20 30",
+            Id::MAX,
+        ))
+        .unwrap();
+
+        assert_eq!(
+            almanac.validate(),
+            vec![
+                Issue {
+                    severity: Severity::Error,
+                    map: "seed-to-soil".to_string(),
+                    line: Some(6),
+                    kind: IssueKind::Overflow {
+                        start: Id::MAX,
+                        len: 2
+                    }
+                },
+                Issue {
+                    severity: Severity::Error,
+                    map: "seed-to-soil".to_string(),
+                    line: Some(11),
+                    kind: IssueKind::DuplicateMap {
+                        first_line: Some(3)
+                    }
+                },
+                Issue {
+                    severity: Severity::Error,
+                    map: "seed-to-soil".to_string(),
+                    line: Some(13),
+                    kind: IssueKind::OverlappingSources {
+                        src: 20..50,
+                        other_src: 15..52,
+                        other_line: Some(12)
+                    }
+                },
+                Issue {
+                    severity: Severity::Warning,
+                    map: "seed-to-soil".to_string(),
+                    line: Some(5),
+                    kind: IssueKind::ZeroLength
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn almanac_new_validate_test() {
+        // overlaps are resolved by Mapper::new before validation gets to see them
+        let almanac = Almanac::new(
+            vec![79, 14],
+            vec![Mapper::new("seed", "soil", vec![(0, 10, 5), (20, 12, 5)]).unwrap()],
+        );
+        assert_eq!(almanac.validate(), vec![]);
+
+        let almanac = Almanac::new(
+            vec![79, 14],
+            vec![
+                Mapper::new("seed", "soil", vec![(0, 10, 5), (20, 12, 5)]).unwrap(),
+                Mapper::new("seed", "soil", vec![(0, 10, 5)]).unwrap(),
+            ],
+        );
+
+        assert_eq!(
+            almanac.validate(),
+            vec![Issue {
+                severity: Severity::Error,
+                map: "seed-to-soil".to_string(),
+                line: None,
+                kind: IssueKind::DuplicateMap { first_line: None }
+            }]
+        );
+    }
+
+    #[test]
+    fn almanac_display_test() {
+        let almanac = Almanac::try_from(String::from(
+            "seeds: 79 14 55 13
+
+                seed-to-soil map:
+                50 98 2
+                52 50 48
+
+                soil-to-water map:
+                0 15 37",
+        ))
+        .unwrap();
+
+        assert_eq!(
+            almanac.to_string(),
+            "seeds: 79 14 55 13
+
+seed-to-soil map:
+52 50 48
+50 98 2
+
+soil-to-water map:
+0 15 37
+"
+        );
+    }
+
+    #[test]
+    fn almanac_round_trip_test() {
+        let almanac = Almanac::try_from(EXAMPLE.to_string()).unwrap();
+        assert_eq!(Almanac::try_from(almanac.to_string()).unwrap(), almanac);
+
+        let empty = Almanac::new(vec![], vec![]);
+        assert_eq!(Almanac::try_from(empty.to_string()).unwrap(), empty);
+    }
+
+    #[test]
+    fn almanac_composed_round_trip_test() {
+        let almanac = Almanac::try_from(EXAMPLE.to_string()).unwrap();
+        let composed = Almanac::new(
+            almanac.seeds.clone(),
+            vec![Almanac::build_map(&almanac.maps, &almanac.graph, "seed", "location").unwrap()],
+        );
+
+        let reparsed = Almanac::try_from(composed.to_string()).unwrap();
+        assert_eq!(reparsed, composed);
+        for seed in [79, 14, 55, 13] {
+            assert_eq!(
+                composed.try_map("seed", seed, "location").unwrap(),
+                almanac.try_map("seed", seed, "location").unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn almanac_build_map_test() {
+        assert!(
+            Almanac::build_map(&[], &CategoryGraph::default(), "nonexistent", "nonexistent")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn almanac_direct_maps_test() {
+        let almanac = Almanac::try_from(EXAMPLE.to_string()).unwrap();
+        let is_cached = |what: &str, to_what: &str| {
+            almanac.direct_maps[&(what.to_string(), to_what.to_string())]
+                .get()
+                .is_some()
+        };
+
+        for category in ["seed", "soil", "water", "humidity", "location"] {
+            assert!(is_cached("seed", category));
+        }
+        assert!(!is_cached("soil", "location"));
+
+        assert!(almanac.try_map("soil", 81, "location").is_ok());
+        assert!(is_cached("soil", "location"));
+        assert!(almanac.try_map("soil", 81, "nonexistent").is_err());
+    }
+
+    mod properties {
+        use crate::almanac::Almanac;
+        use crate::mapper::{Id, Mapper};
+        use proptest::collection::vec;
+        use proptest::prelude::*;
+        use std::ops::Range;
+
+        fn almanacs() -> impl Strategy<Value = Almanac> {
+            let categories = ["seed", "soil", "fertilizer", "water", "location"];
+
+            (
+                vec(any::<Id>(), 0..10),
+                vec(
+                    vec((0..Id::MAX / 2, 0..Id::MAX / 2, 0..Id::MAX / 2), 0..6),
+                    0..categories.len(),
+                ),
+            )
+                .prop_map(move |(seeds, maps)| {
+                    Almanac::new(
+                        seeds,
+                        maps.into_iter()
+                            .enumerate()
+                            .map(|(idx, ranges)| {
+                                Mapper::new(categories[idx], categories[idx + 1], ranges).unwrap()
+                            })
+                            .collect(),
+                    )
+                })
+        }
+
+        /// Almanacs with the whole chain from seeds to locations in a small domain, so that seeds can be brute-forced.
+        fn small_chain_almanacs() -> impl Strategy<Value = Almanac> {
+            let categories = ["seed", "soil", "fertilizer", "water", "location"];
+
+            vec(
+                vec((0..200 as Id, 0..200 as Id, 0..50 as Id), 0..6),
+                categories.len() - 1,
+            )
+            .prop_map(move |maps| {
+                Almanac::new(
+                    vec![],
+                    maps.into_iter()
+                        .enumerate()
+                        .map(|(idx, ranges)| {
+                            Mapper::new(categories[idx], categories[idx + 1], ranges).unwrap()
+                        })
+                        .collect(),
+                )
+            })
+        }
+
+        proptest! {
+            #[test]
+            fn almanac_min_location_matches_brute_force_test(
+                almanac in small_chain_almanacs(),
+                seeds in vec((0..250 as Id, 0..50 as Id), 0..4),
+            ) {
+                let seeds: Vec<Range<Id>> = seeds.into_iter().map(|(start, len)| start..(start + len)).collect();
+
+                let expected = seeds
+                    .iter()
+                    .flat_map(|r| r.clone())
+                    .map(|seed| (almanac.try_map("seed", seed, "location").unwrap(), seed))
+                    .min()
+                    .map(|(location, seed)| (seed, location));
+
+                prop_assert_eq!(almanac.min_location(&seeds), expected);
+            }
+
+            #[test]
+            fn almanac_round_trip_test(almanac in almanacs()) {
+                let printed = almanac.to_string();
+
+                prop_assert_eq!(Almanac::try_from(printed.clone()).unwrap(), almanac);
+                prop_assert_eq!(Almanac::try_from(printed.clone()).unwrap().to_string(), printed);
+            }
+        }
+    }
 }
@@ -0,0 +1,137 @@
+use crate::mapper::{merge_ranges, Id, Mapper};
+use anyhow::Result;
+use indicatif::ProgressBar;
+use rayon::prelude::*;
+use rayon::ThreadPoolBuilder;
+use std::cmp::min;
+use std::num::NonZeroUsize;
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Number of seeds a thread maps before checking for cancellation and reporting progress.
+const CHUNK_LEN: usize = 1 << 20;
+
+#[derive(Debug, PartialEq)]
+pub struct BruteForceResult {
+    /// Seed with the closest location among the checked ones, as `(seed, location)`.
+    pub closest: Option<(Id, Id)>,
+    /// Whether every seed was checked before cancellation.
+    pub complete: bool,
+}
+
+/// Maps every single seed of `seed_ranges` through `maps` one after another on `threads` threads to find the closest
+/// location.
+///
+/// Seeds go through every map of the chain rather than through a composed map, so that the result can verify
+/// composition. Stops early once `cancelled` is set, returning the closest location among the seeds checked so far.
+pub fn min_location(
+    maps: &[&Mapper],
+    seed_ranges: &[Range<Id>],
+    threads: NonZeroUsize,
+    cancelled: &AtomicBool,
+    progress: &ProgressBar,
+) -> Result<BruteForceResult> {
+    // chunks as start and length, so that lengths fit into usize whatever the id type is
+    let chunks: Vec<(Id, usize)> = merge_ranges(seed_ranges.to_vec())
+        .into_iter()
+        .flat_map(|r| {
+            (r.start..r.end)
+                .step_by(CHUNK_LEN)
+                .map(move |start| (start, min(CHUNK_LEN as Id, r.end - start) as usize))
+        })
+        .collect();
+
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(threads.get())
+        .build()?;
+    let closest = pool.install(|| {
+        chunks
+            .par_iter()
+            .filter_map(|&(start, len)| {
+                if cancelled.load(Ordering::Relaxed) {
+                    return None;
+                }
+
+                let closest = (start..(start + len as Id))
+                    .map(|seed| (maps.iter().fold(seed, |id, map| map.map(id)), seed))
+                    .min();
+                progress.inc(len as u64);
+
+                closest
+            })
+            .min()
+    });
+
+    Ok(BruteForceResult {
+        closest: closest.map(|(location, seed)| (seed, location)),
+        complete: !cancelled.load(Ordering::Relaxed),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{min_location, BruteForceResult};
+    use crate::mapper::Mapper;
+    use indicatif::ProgressBar;
+    use std::num::NonZeroUsize;
+    use std::sync::atomic::AtomicBool;
+
+    #[test]
+    fn brute_force_min_location_test() {
+        let mapper = Mapper::new("seed", "location", vec![(50, 98, 2), (52, 50, 48)]).unwrap();
+        let threads = NonZeroUsize::new(2).unwrap();
+        let progress = ProgressBar::hidden();
+
+        assert_eq!(
+            min_location(
+                &[&mapper],
+                &[60..70, 95..2_000_000],
+                threads,
+                &AtomicBool::new(false),
+                &progress
+            )
+            .unwrap(),
+            BruteForceResult {
+                closest: Some((98, 50)),
+                complete: true
+            }
+        );
+        assert_eq!(progress.position(), 10 + 2_000_000 - 95);
+
+        assert_eq!(
+            min_location(
+                &[&mapper],
+                &[60..70, 80..90],
+                threads,
+                &AtomicBool::new(true),
+                &progress
+            )
+            .unwrap(),
+            BruteForceResult {
+                closest: None,
+                complete: false
+            }
+        );
+
+        let next = Mapper::new(
+            "location",
+            "far",
+            vec![(0, 15, 37), (37, 52, 2), (39, 0, 15)],
+        )
+        .unwrap();
+        assert_eq!(
+            min_location(
+                &[&mapper, &next],
+                &[79..80, 14..15],
+                threads,
+                &AtomicBool::new(false),
+                &progress
+            )
+            .unwrap(),
+            BruteForceResult {
+                closest: Some((14, 53)),
+                complete: true
+            }
+        );
+    }
+}
@@ -0,0 +1,176 @@
+use crate::almanac::Almanac;
+use crate::mapper::Id;
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// Escapes a category name for use in a quoted DOT id or label.
+fn escape(name: &str) -> String {
+    name.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Renders categories of the almanac as Graphviz DOT nodes and ranges of its maps as edges between them.
+///
+/// With a `seed`, every category reachable from seeds is labeled with the id the seed maps to there, and the ranges
+/// it goes through are highlighted.
+pub fn to_dot(almanac: &Almanac, seed: Option<Id>) -> String {
+    let mut values: HashMap<&str, Id> = HashMap::new();
+    if let Some(seed) = seed {
+        values.insert("seed", seed);
+
+        let mut queue = vec!["seed"];
+        while let Some(category) = queue.pop() {
+            for map in almanac.maps().iter().filter(|m| m.source == category) {
+                if !values.contains_key(map.destination.as_str()) {
+                    values.insert(&map.destination, map.map(values[category]));
+                    queue.push(&map.destination);
+                }
+            }
+        }
+    }
+
+    let mut categories: Vec<&str> = vec![];
+    for map in almanac.maps() {
+        for category in [&map.source, &map.destination] {
+            if !categories.contains(&category.as_str()) {
+                categories.push(category);
+            }
+        }
+    }
+
+    // writing into a String never fails
+    let mut dot = String::new();
+    writeln!(dot, "digraph almanac {{").unwrap();
+    writeln!(dot, "    rankdir=LR;").unwrap();
+    writeln!(dot, "    node [shape=box];").unwrap();
+
+    for category in categories {
+        let name = escape(category);
+        match values.get(category) {
+            Some(value) => writeln!(
+                dot,
+                "    \"{}\" [label=\"{}\\n{}\", color=red];",
+                name, name, value
+            ),
+            None => writeln!(dot, "    \"{}\";", name),
+        }
+        .unwrap();
+    }
+
+    for map in almanac.maps() {
+        let (source, destination) = (escape(&map.source), escape(&map.destination));
+        let hit = values
+            .get(map.source.as_str())
+            .map(|&value| map.matching_range(value));
+        let highlight = |range| {
+            if hit == Some(range) {
+                ", color=red, penwidth=2"
+            } else {
+                ""
+            }
+        };
+
+        for (dst_start, src_start, len) in map.ranges() {
+            writeln!(
+                dot,
+                "    \"{}\" -> \"{}\" [label=\"{:?} -> {:?}\"{}];",
+                source,
+                destination,
+                src_start..(src_start + len),
+                dst_start..(dst_start + len),
+                highlight(Some((dst_start, src_start, len)))
+            )
+            .unwrap();
+        }
+        writeln!(
+            dot,
+            "    \"{}\" -> \"{}\" [label=\"otherwise identity\", style=dashed{}];",
+            source,
+            destination,
+            highlight(None)
+        )
+        .unwrap();
+    }
+
+    writeln!(dot, "}}").unwrap();
+
+    dot
+}
+
+#[cfg(test)]
+mod test {
+    use super::to_dot;
+    use crate::almanac::Almanac;
+
+    const ALMANAC: &str = "seeds: 79 14
+
+seed-to-soil map:
+50 98 2
+52 50 48
+
+soil-to-location map:
+0 15 37";
+
+    #[test]
+    fn to_dot_test() {
+        let almanac = Almanac::try_from(ALMANAC.to_string()).unwrap();
+
+        assert_eq!(
+            to_dot(&almanac, None),
+            r#"digraph almanac {
+    rankdir=LR;
+    node [shape=box];
+    "seed";
+    "soil";
+    "location";
+    "seed" -> "soil" [label="50..98 -> 52..100"];
+    "seed" -> "soil" [label="98..100 -> 50..52"];
+    "seed" -> "soil" [label="otherwise identity", style=dashed];
+    "soil" -> "location" [label="15..52 -> 0..37"];
+    "soil" -> "location" [label="otherwise identity", style=dashed];
+}
+"#
+        );
+    }
+
+    #[test]
+    fn to_dot_seed_test() {
+        let almanac = Almanac::try_from(ALMANAC.to_string()).unwrap();
+
+        assert_eq!(
+            to_dot(&almanac, Some(99)),
+            r#"digraph almanac {
+    rankdir=LR;
+    node [shape=box];
+    "seed" [label="seed\n99", color=red];
+    "soil" [label="soil\n51", color=red];
+    "location" [label="location\n36", color=red];
+    "seed" -> "soil" [label="50..98 -> 52..100"];
+    "seed" -> "soil" [label="98..100 -> 50..52", color=red, penwidth=2];
+    "seed" -> "soil" [label="otherwise identity", style=dashed];
+    "soil" -> "location" [label="15..52 -> 0..37", color=red, penwidth=2];
+    "soil" -> "location" [label="otherwise identity", style=dashed];
+}
+"#
+        );
+
+        assert!(to_dot(&almanac, Some(10)).contains(
+            r#""seed" -> "soil" [label="otherwise identity", style=dashed, color=red, penwidth=2];"#
+        ));
+    }
+
+    #[test]
+    fn to_dot_escape_test() {
+        let almanac = Almanac::try_from(
+            r#"seeds: 1
+
+"seed"-to-back\slash map:
+0 1 1"#
+                .to_string(),
+        )
+        .unwrap();
+
+        let dot = to_dot(&almanac, None);
+        assert!(dot.contains(r#"    "\"seed\"";"#));
+        assert!(dot.contains(r#"    "\"seed\"" -> "back\\slash" [label="1..2 -> 0..1"];"#));
+    }
+}
@@ -0,0 +1,351 @@
+use crate::mapper::Id;
+use anyhow::{anyhow, Result};
+use std::cmp::{max, min};
+use std::fmt::Write;
+use std::mem::size_of;
+use std::ops::Range;
+
+const MIDDLE_CATEGORIES: [&str; 6] = [
+    "soil",
+    "fertilizer",
+    "water",
+    "light",
+    "temperature",
+    "humidity",
+];
+
+/// Shape of the almanacs to generate.
+#[derive(Debug, Clone)]
+pub struct GeneratorConfig {
+    /// Seed of the random generator, the same seed and settings always give the same almanac.
+    pub seed: u64,
+    /// Number of categories from seeds to locations, both included.
+    pub categories: usize,
+    /// Number of pieces the ids of a category are cut into for every map, some of them become gaps.
+    pub ranges_per_map: usize,
+    /// Ids of every category are below this value.
+    pub magnitude: Id,
+    /// Chance of a range to map onto a random place, likely overlapping with other destination ranges.
+    pub overlap: f64,
+    /// Chance of a piece to be left out of the map, so that it maps ids to themselves.
+    pub gap: f64,
+    /// Number of pairs of seed range start and length.
+    pub seed_ranges: usize,
+}
+
+impl Default for GeneratorConfig {
+    /// Settings resembling real puzzle inputs.
+    fn default() -> Self {
+        GeneratorConfig {
+            seed: 0,
+            categories: 8,
+            ranges_per_map: 40,
+            magnitude: 1 << 32,
+            overlap: 0.0,
+            gap: 0.1,
+            seed_ranges: 10,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct GeneratedAlmanac {
+    /// Almanac in the puzzle text format.
+    pub text: String,
+    /// Closest locations for seeds read as individual seeds and as seed ranges.
+    pub expected: (Id, Id),
+}
+
+/// SplitMix64 generator, small enough to keep generated almanacs stable regardless of dependency updates.
+///
+/// Public so that other generated inputs, like those of benchmarks, stay just as stable.
+pub struct Random(u64);
+
+impl Random {
+    pub fn new(seed: u64) -> Self {
+        Random(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// Returns a number in `0..=max`.
+    fn up_to(&mut self, max: Id) -> Id {
+        let wide = (0..size_of::<Id>() / size_of::<u64>()).fold(0, |acc: Id, _| {
+            acc.checked_shl(64).unwrap_or(0) | self.next_u64() as Id
+        });
+
+        match max.checked_add(1) {
+            Some(bound) => wide % bound,
+            None => wide,
+        }
+    }
+
+    fn chance(&mut self, probability: f64) -> bool {
+        ((self.next_u64() >> 11) as f64 / (1_u64 << 53) as f64) < probability
+    }
+
+    fn shuffle<T>(&mut self, items: &mut [T]) {
+        for idx in (1..items.len()).rev() {
+            items.swap(idx, self.up_to(idx as Id) as usize);
+        }
+    }
+}
+
+fn category(idx: usize, count: usize) -> String {
+    match idx {
+        0 => "seed".to_string(),
+        _ if idx == count - 1 => "location".to_string(),
+        _ if idx <= MIDDLE_CATEGORIES.len() => MIDDLE_CATEGORIES[idx - 1].to_string(),
+        _ => format!("stage{}", idx),
+    }
+}
+
+/// Generates a random almanac which passes validation, along with answers computed by `reference_min_location`.
+pub fn generate(config: &GeneratorConfig) -> Result<GeneratedAlmanac> {
+    if config.categories < 2 {
+        Err(anyhow!("At least seed and location categories are needed"))?
+    }
+    if config.ranges_per_map == 0 || config.magnitude < config.ranges_per_map as Id {
+        Err(anyhow!(
+            "Cannot cut {} ids into {} ranges",
+            config.magnitude,
+            config.ranges_per_map
+        ))?
+    }
+    for (name, probability) in [("Overlap", config.overlap), ("Gap", config.gap)] {
+        if !(0.0..=1.0).contains(&probability) {
+            Err(anyhow!(
+                "{} density {} is not a probability",
+                name,
+                probability
+            ))?
+        }
+    }
+
+    let mut random = Random::new(config.seed);
+
+    let max_seed_range_len = max(1, config.magnitude / (4 * max(1, config.seed_ranges) as Id));
+    let seeds: Vec<Id> = (0..config.seed_ranges)
+        .flat_map(|_| {
+            let len = 1 + random.up_to(max_seed_range_len - 1);
+            [random.up_to(config.magnitude - len), len]
+        })
+        .collect();
+
+    let mut maps = vec![];
+    for _ in 1..config.categories {
+        let mut cuts: Vec<Id> = (1..config.ranges_per_map)
+            .map(|_| 1 + random.up_to(config.magnitude - 2))
+            .collect();
+        cuts.extend([0, config.magnitude]);
+        cuts.sort();
+        cuts.dedup();
+
+        let srcs: Vec<Range<Id>> = cuts
+            .windows(2)
+            .map(|w| w[0]..w[1])
+            .filter(|_| !random.chance(config.gap))
+            .collect();
+
+        // lay destinations out next to each other in shuffled order, like puzzle inputs do
+        let mut order: Vec<usize> = (0..srcs.len()).collect();
+        random.shuffle(&mut order);
+        let total: Id = srcs.iter().map(|src| src.end - src.start).sum();
+        let mut dst_start = random.up_to(config.magnitude - total);
+        let mut ranges = vec![(0, 0, 0); srcs.len()];
+        for src_idx in order {
+            let src = &srcs[src_idx];
+            let len = src.end - src.start;
+            let dst = if random.chance(config.overlap) {
+                random.up_to(config.magnitude - len)
+            } else {
+                dst_start
+            };
+            ranges[src_idx] = (dst, src.start, len);
+            dst_start += len;
+        }
+        random.shuffle(&mut ranges);
+
+        maps.push(ranges);
+    }
+
+    let individual_seeds: Vec<Range<Id>> = seeds.iter().map(|&seed| seed..(seed + 1)).collect();
+    let seed_ranges: Vec<Range<Id>> = seeds
+        .chunks_exact(2)
+        .map(|pair| pair[0]..(pair[0] + pair[1]))
+        .collect();
+    let expected = match (
+        reference_min_location(&maps, &individual_seeds),
+        reference_min_location(&maps, &seed_ranges),
+    ) {
+        (Some(individual), Some(ranges)) => (individual, ranges),
+        _ => Err(anyhow!("At least one seed range is needed to have answers"))?,
+    };
+
+    // writing into a String never fails
+    let mut text = String::new();
+    write!(
+        text,
+        "seeds: {}",
+        seeds
+            .iter()
+            .map(|seed| seed.to_string())
+            .collect::<Vec<String>>()
+            .join(" ")
+    )
+    .unwrap();
+    for (idx, ranges) in maps.into_iter().enumerate() {
+        write!(
+            text,
+            "\n\n{}-to-{} map:",
+            category(idx, config.categories),
+            category(idx + 1, config.categories)
+        )
+        .unwrap();
+        for (dst_start, src_start, len) in ranges {
+            write!(text, "\n{} {} {}", dst_start, src_start, len).unwrap();
+        }
+    }
+    writeln!(text).unwrap();
+
+    Ok(GeneratedAlmanac { text, expected })
+}
+
+/// Finds the closest location the plain way: splitting ranges against every range definition of every map in turn.
+///
+/// Maps are given by their range definitions in the order of the chain from seeds to locations. Shares no code
+/// with `Mapper`, so that answers of the fast paths can be checked against it.
+pub fn reference_min_location(maps: &[Vec<(Id, Id, Id)>], seed_ranges: &[Range<Id>]) -> Option<Id> {
+    let mut ranges = seed_ranges.to_vec();
+
+    for definitions in maps {
+        let mut mapped = vec![];
+        while let Some(range) = ranges.pop() {
+            let overlapping = definitions.iter().find(|&&(_dst_start, src_start, len)| {
+                max(range.start, src_start) < min(range.end, src_start + len)
+            });
+
+            match overlapping {
+                Some(&(dst_start, src_start, len)) => {
+                    let start = max(range.start, src_start);
+                    let end = min(range.end, src_start + len);
+                    mapped.push((dst_start + (start - src_start))..(dst_start + (end - src_start)));
+
+                    if range.start < start {
+                        ranges.push(range.start..start);
+                    }
+                    if end < range.end {
+                        ranges.push(end..range.end);
+                    }
+                }
+                None => mapped.push(range),
+            }
+        }
+        ranges = mapped;
+    }
+
+    ranges.iter().map(|r| r.start).min()
+}
+
+#[cfg(test)]
+mod test {
+    use super::{generate, GeneratorConfig};
+    use crate::almanac::{Almanac, SeedMode};
+
+    #[test]
+    fn generate_reproducible_test() {
+        let config = GeneratorConfig {
+            seed: 7,
+            ..Default::default()
+        };
+
+        assert_eq!(
+            generate(&config).unwrap().text,
+            generate(&config).unwrap().text
+        );
+        assert_ne!(
+            generate(&config).unwrap().text,
+            generate(&GeneratorConfig { seed: 8, ..config })
+                .unwrap()
+                .text
+        );
+    }
+
+    #[test]
+    fn generate_valid_almanac_test() {
+        for seed in 0..20 {
+            for (overlap, gap) in [(0.0, 0.0), (0.0, 0.5), (0.5, 0.2), (1.0, 1.0)] {
+                let config = GeneratorConfig {
+                    seed,
+                    categories: 2 + seed as usize % 10,
+                    ranges_per_map: 1 + seed as usize * 3,
+                    magnitude: 1000,
+                    overlap,
+                    gap,
+                    seed_ranges: 1 + seed as usize % 4,
+                };
+                let generated = generate(&config).unwrap();
+                let almanac = Almanac::try_from(generated.text.clone()).unwrap();
+
+                assert_eq!(almanac.validate(), vec![], "{}", generated.text);
+                assert_eq!(almanac.seeds.len(), 2 * config.seed_ranges);
+                assert_eq!(
+                    almanac.paths("seed", "location").unwrap().len(),
+                    1,
+                    "{}",
+                    generated.text
+                );
+
+                for (mode, expected) in [
+                    (SeedMode::Individual, generated.expected.0),
+                    (SeedMode::Ranges, generated.expected.1),
+                ] {
+                    let seeds = almanac.seed_ranges(mode).unwrap();
+                    assert_eq!(
+                        almanac
+                            .min_location(&seeds)
+                            .map(|(_seed, location)| location),
+                        Some(expected),
+                        "{}",
+                        generated.text
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn generate_invalid_config_test() {
+        for config in [
+            GeneratorConfig {
+                categories: 1,
+                ..Default::default()
+            },
+            GeneratorConfig {
+                ranges_per_map: 0,
+                ..Default::default()
+            },
+            GeneratorConfig {
+                magnitude: 10,
+                ranges_per_map: 11,
+                ..Default::default()
+            },
+            GeneratorConfig {
+                gap: 1.5,
+                ..Default::default()
+            },
+            GeneratorConfig {
+                seed_ranges: 0,
+                ..Default::default()
+            },
+        ] {
+            assert!(generate(&config).is_err(), "{:?}", config);
+        }
+    }
+}
@@ -0,0 +1,312 @@
+use crate::mapper::Mapper;
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+
+#[derive(Debug, PartialEq)]
+pub enum PathError {
+    UnknownCategory(String),
+    Unreachable {
+        from: String,
+        to: String,
+    },
+    Cycle(Vec<String>),
+    Ambiguous {
+        from: String,
+        to: String,
+        paths: Vec<Vec<String>>,
+    },
+}
+
+impl Display for PathError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PathError::UnknownCategory(category) => {
+                write!(f, "Category {} is not mapped by any map", category)
+            }
+            PathError::Unreachable { from, to } => {
+                write!(f, "No chain of maps leads from {} to {}", from, to)
+            }
+            PathError::Cycle(categories) => {
+                write!(f, "Maps form a cycle: {}", categories.join(" -> "))
+            }
+            PathError::Ambiguous { from, to, paths } => write!(
+                f,
+                "Several chains of maps lead from {} to {}: {}",
+                from,
+                to,
+                paths
+                    .iter()
+                    .map(|p| p.join(" -> "))
+                    .collect::<Vec<String>>()
+                    .join(", ")
+            ),
+        }
+    }
+}
+
+impl std::error::Error for PathError {}
+
+/// Directed graph of almanac categories with maps as its edges.
+#[derive(Debug, Default)]
+pub struct CategoryGraph {
+    links: Vec<(String, String)>,
+    edges: HashMap<String, Vec<usize>>,
+}
+
+impl CategoryGraph {
+    /// Builds the graph, referring to maps by their indices in `maps`.
+    pub fn new(maps: &[Mapper]) -> Self {
+        let mut edges: HashMap<String, Vec<usize>> = HashMap::new();
+        for (idx, map) in maps.iter().enumerate() {
+            edges.entry(map.source.clone()).or_default().push(idx);
+            edges.entry(map.destination.clone()).or_default();
+        }
+
+        CategoryGraph {
+            links: maps
+                .iter()
+                .map(|m| (m.source.clone(), m.destination.clone()))
+                .collect(),
+            edges,
+        }
+    }
+
+    pub fn contains(&self, category: &str) -> bool {
+        self.edges.contains_key(category)
+    }
+
+    pub fn categories(&self) -> impl Iterator<Item = &str> {
+        self.edges.keys().map(String::as_str)
+    }
+
+    /// Finds every path from `from` to `to` as a list of map indices.
+    ///
+    /// Fails if there is no such path or if the maps leading to `to` form a cycle, as then there are infinitely many.
+    pub fn paths(&self, from: &str, to: &str) -> Result<Vec<Vec<usize>>, PathError> {
+        for category in [from, to] {
+            if !self.contains(category) {
+                return Err(PathError::UnknownCategory(category.to_string()));
+            }
+        }
+
+        // only the categories `to` is reachable from are worth visiting
+        let mut leads_to_target = HashSet::from([to]);
+        let mut queue = vec![to];
+        while let Some(category) = queue.pop() {
+            for (src, _dst) in self.links.iter().filter(|(_src, dst)| dst == category) {
+                if leads_to_target.insert(src) {
+                    queue.push(src);
+                }
+            }
+        }
+
+        let mut result = vec![];
+        if leads_to_target.contains(from) {
+            self.walk(
+                from,
+                to,
+                &leads_to_target,
+                &mut vec![],
+                &mut vec![],
+                &mut result,
+            )?;
+        }
+
+        if result.is_empty() {
+            Err(PathError::Unreachable {
+                from: from.to_string(),
+                to: to.to_string(),
+            })
+        } else {
+            Ok(result)
+        }
+    }
+
+    /// Finds the only path from `from` to `to`.
+    pub fn path(&self, from: &str, to: &str) -> Result<Vec<usize>, PathError> {
+        let mut paths = self.paths(from, to)?;
+
+        if paths.len() > 1 {
+            Err(PathError::Ambiguous {
+                from: from.to_string(),
+                to: to.to_string(),
+                paths: paths.iter().map(|p| self.categories_of(from, p)).collect(),
+            })
+        } else {
+            Ok(paths.remove(0))
+        }
+    }
+
+    /// Lists categories visited along the path of map indices starting at `from`.
+    pub fn categories_of(&self, from: &str, path: &[usize]) -> Vec<String> {
+        let mut result = vec![from.to_string()];
+        result.extend(path.iter().map(|&idx| self.links[idx].1.clone()));
+
+        result
+    }
+
+    fn walk<'a>(
+        &'a self,
+        curr: &'a str,
+        to: &str,
+        leads_to_target: &HashSet<&str>,
+        visiting: &mut Vec<&'a str>,
+        path: &mut Vec<usize>,
+        result: &mut Vec<Vec<usize>>,
+    ) -> Result<(), PathError> {
+        if curr == to {
+            result.push(path.clone());
+            return Ok(());
+        }
+
+        visiting.push(curr);
+        for &idx in self.edges[curr].iter() {
+            let next = self.links[idx].1.as_str();
+            if !leads_to_target.contains(next) {
+                continue;
+            }
+
+            if let Some(pos) = visiting.iter().position(|&c| c == next) {
+                let mut cycle: Vec<String> =
+                    visiting[pos..].iter().map(|c| c.to_string()).collect();
+                cycle.push(next.to_string());
+
+                return Err(PathError::Cycle(cycle));
+            }
+
+            path.push(idx);
+            self.walk(next, to, leads_to_target, visiting, path, result)?;
+            path.pop();
+        }
+        visiting.pop();
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{CategoryGraph, PathError};
+    use crate::mapper::Mapper;
+
+    fn graph(links: &[(&str, &str)]) -> CategoryGraph {
+        CategoryGraph::new(
+            &links
+                .iter()
+                .map(|(s, d)| Mapper::identity(s, d))
+                .collect::<Vec<Mapper>>(),
+        )
+    }
+
+    #[test]
+    fn graph_chain_path_test() {
+        let g = graph(&[("soil", "water"), ("seed", "soil"), ("water", "location")]);
+
+        assert_eq!(g.paths("seed", "location"), Ok(vec![vec![1, 0, 2]]));
+        assert_eq!(g.path("soil", "location"), Ok(vec![0, 2]));
+        assert_eq!(g.path("seed", "seed"), Ok(vec![]));
+        assert_eq!(
+            g.categories_of("seed", &[1, 0]),
+            vec!["seed", "soil", "water"]
+        );
+    }
+
+    #[test]
+    fn graph_branching_paths_test() {
+        let g = graph(&[
+            ("seed", "soil"),
+            ("seed", "light"),
+            ("soil", "location"),
+            ("light", "location"),
+            ("light", "humidity"),
+        ]);
+
+        assert_eq!(g.path("seed", "humidity"), Ok(vec![1, 4]));
+        assert_eq!(
+            g.paths("seed", "location"),
+            Ok(vec![vec![0, 2], vec![1, 3]])
+        );
+        assert_eq!(
+            g.path("seed", "location"),
+            Err(PathError::Ambiguous {
+                from: "seed".to_string(),
+                to: "location".to_string(),
+                paths: vec![
+                    vec![
+                        "seed".to_string(),
+                        "soil".to_string(),
+                        "location".to_string()
+                    ],
+                    vec![
+                        "seed".to_string(),
+                        "light".to_string(),
+                        "location".to_string()
+                    ]
+                ]
+            })
+        );
+    }
+
+    #[test]
+    fn graph_unreachable_test() {
+        let g = graph(&[("seed", "soil"), ("light", "location")]);
+
+        assert_eq!(
+            g.paths("seed", "location"),
+            Err(PathError::Unreachable {
+                from: "seed".to_string(),
+                to: "location".to_string()
+            })
+        );
+        assert_eq!(
+            g.paths("location", "seed"),
+            Err(PathError::Unreachable {
+                from: "location".to_string(),
+                to: "seed".to_string()
+            })
+        );
+        assert_eq!(
+            g.paths("seed", "nonexistent"),
+            Err(PathError::UnknownCategory("nonexistent".to_string()))
+        );
+    }
+
+    #[test]
+    fn graph_cycle_test() {
+        let g = graph(&[
+            ("seed", "soil"),
+            ("soil", "water"),
+            ("water", "soil"),
+            ("water", "location"),
+            ("seed", "light"),
+            ("light", "seed"),
+        ]);
+
+        assert_eq!(
+            g.paths("seed", "location"),
+            Err(PathError::Cycle(vec![
+                "soil".to_string(),
+                "water".to_string(),
+                "soil".to_string()
+            ]))
+        );
+    }
+
+    #[test]
+    fn graph_cycle_away_from_target_test() {
+        let g = graph(&[
+            ("seed", "soil"),
+            ("seed", "location"),
+            ("location", "humidity"),
+            ("humidity", "location"),
+            ("soil", "fertilizer"),
+            ("fertilizer", "soil"),
+        ]);
+
+        assert_eq!(g.paths("seed", "location"), Ok(vec![vec![1]]));
+        assert_eq!(
+            g.path("light", "location"),
+            Err(PathError::UnknownCategory("light".to_string()))
+        );
+    }
+}
@@ -0,0 +1,23 @@
+//! Range mapping behind the "If You Give A Seed A Fertilizer" puzzle, usable outside of the day5 binary.
+//!
+//! `Mapper` maps ids of one category to another, `Almanac` parses chains of them and finds the closest locations
+//! to plant seeds at. Ids are `u64`, or `u128` with the `u128` feature.
+
+pub mod almanac;
+pub mod brute_force;
+pub mod dot;
+pub mod generator;
+pub mod graph;
+pub mod mapper;
+pub mod monotone;
+pub mod parser;
+#[cfg(feature = "serde")]
+pub mod serialization;
+mod solver;
+pub mod validation;
+
+pub use almanac::{Almanac, SeedMode};
+pub use mapper::{checked_range, merge_ranges, Id, Key, Mapper, RangeError, RangeMapper};
+pub use monotone::MonotoneMapper;
+pub use parser::{AlmanacParseError, AlmanacReader};
+pub use solver::Day5;
@@ -1,38 +1,263 @@
-mod almanac;
-mod mapper;
+use anyhow::{anyhow, Result};
+use clap::{Parser, Subcommand};
+use day5::brute_force::{self, BruteForceResult};
+use day5::dot;
+use day5::generator::{self, GeneratorConfig};
+use day5::validation::Severity;
+use day5::{checked_range, merge_ranges, Almanac, Id, SeedMode};
+use indicatif::{ProgressBar, ProgressStyle};
+use std::cmp::min;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader};
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
+use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
-use crate::almanac::Almanac;
-use anyhow::Result;
-use std::fs;
+const INPUT: &str = "input.txt";
 
-fn main() -> Result<()> {
-    let almanac_def = fs::read_to_string("input.txt")?;
+#[derive(Parser)]
+#[command(about = "Finds the closest location to plant initial seeds at")]
+struct Cli {
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Write the chain of maps as a Graphviz DOT graph to FILE instead of solving the almanac
+    #[arg(long, value_name = "FILE")]
+    dot: Option<PathBuf>,
+
+    /// Highlight how this seed goes through the maps in the DOT graph
+    #[arg(long, requires = "dot")]
+    seed: Option<Id>,
+
+    /// Check every single seed on THREADS threads instead of mapping whole ranges, to verify the answers
+    #[arg(long, value_name = "THREADS")]
+    brute_force: Option<NonZeroUsize>,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Write a random almanac to FILE and print the answers expected for it
+    Generate {
+        #[arg(value_name = "FILE")]
+        output: PathBuf,
+
+        /// Seed of the random generator, the same seed and settings always give the same almanac
+        #[arg(long, default_value_t = GeneratorConfig::default().seed)]
+        random_seed: u64,
+
+        /// Number of categories from seeds to locations, both included
+        #[arg(long, default_value_t = GeneratorConfig::default().categories)]
+        categories: usize,
+
+        /// Number of pieces ids are cut into for every map, some of them become gaps
+        #[arg(long, default_value_t = GeneratorConfig::default().ranges_per_map)]
+        ranges_per_map: usize,
+
+        /// Ids of every category are below this value
+        #[arg(long, default_value_t = GeneratorConfig::default().magnitude)]
+        magnitude: Id,
+
+        /// Chance of a range to map onto a random place, likely overlapping with other destination ranges
+        #[arg(long, default_value_t = GeneratorConfig::default().overlap)]
+        overlap: f64,
+
+        /// Chance of a piece to be left out of the map, so that it maps ids to themselves
+        #[arg(long, default_value_t = GeneratorConfig::default().gap)]
+        gap: f64,
+
+        /// Number of pairs of seed range start and length
+        #[arg(long, default_value_t = GeneratorConfig::default().seed_ranges)]
+        seed_ranges: usize,
+    },
+
+    /// Print every map a seed goes through on its way to a location, and which range of the map moves it
+    Explain {
+        seed: Id,
+
+        /// Trace the whole range of LEN seeds starting at SEED, showing how it splits between map ranges
+        #[arg(long, default_value_t = 1)]
+        len: Id,
+    },
+}
+
+/// Finds the closest locations by mapping seeds one by one, reporting the closest one so far on Ctrl-C.
+fn solve_brute_force(almanac: &Almanac, threads: NonZeroUsize) -> Result<()> {
+    let cancelled = Arc::new(AtomicBool::new(false));
+    let handler_cancelled = cancelled.clone();
+    ctrlc::set_handler(move || handler_cancelled.store(true, Ordering::Relaxed))?;
+
+    for mode in [SeedMode::Individual, SeedMode::Ranges] {
+        let seeds = almanac.seed_ranges(mode)?;
+        let seeds_count: Id = merge_ranges(seeds.clone())
+            .iter()
+            .map(|r| r.end - r.start)
+            .sum();
+
+        let progress = ProgressBar::new(min(seeds_count, u64::MAX as Id) as u64)
+            .with_style(ProgressStyle::with_template(
+                "{msg} [{elapsed_precise}] {wide_bar} {human_pos}/{human_len} seeds, {per_sec}, ETA {eta}",
+            )?)
+            .with_message(format!("Checking initial {}", mode));
+        let result = brute_force::min_location(
+            &almanac.chain("seed", "location")?,
+            &seeds,
+            threads,
+            &cancelled,
+            &progress,
+        )?;
+        let checked = progress.position();
+        progress.finish_and_clear();
+
+        match result {
+            BruteForceResult {
+                closest: Some((seed, closest_location)),
+                complete,
+            } => {
+                println!(
+                    "Closest location for initial {} {}is {}, where seed {} goes",
+                    mode,
+                    if complete { "" } else { "found so far " },
+                    closest_location,
+                    seed
+                );
+            }
+            BruteForceResult {
+                closest: None,
+                complete: true,
+            } => Err(anyhow!("No initial seeds in almanac"))?,
+            BruteForceResult {
+                closest: None,
+                complete: false,
+            } => {}
+        }
+
+        if !result.complete {
+            println!(
+                "Cancelled after checking {} of {} initial {}",
+                checked, seeds_count, mode
+            );
+            break;
+        }
+    }
+
+    Ok(())
+}
 
-    let mut almanac: Almanac = almanac_def.try_into()?;
+fn main() -> Result<()> {
+    let cli = Cli::parse();
 
-    let mut closest_location = u64::MAX;
-    let mut seed_range_idx = 0;
-    while seed_range_idx < almanac.seeds.len() {
-        let seed_range = &almanac.seeds[seed_range_idx];
+    if let Some(Command::Generate {
+        output,
+        random_seed,
+        categories,
+        ranges_per_map,
+        magnitude,
+        overlap,
+        gap,
+        seed_ranges,
+    }) = cli.command
+    {
+        let generated = generator::generate(&GeneratorConfig {
+            seed: random_seed,
+            categories,
+            ranges_per_map,
+            magnitude,
+            overlap,
+            gap,
+            seed_ranges,
+        })?;
+        fs::write(&output, generated.text)?;
+        println!("Wrote generated almanac to {}", output.display());
+        println!(
+            "Expected closest location for initial {} is {}",
+            SeedMode::Individual,
+            generated.expected.0
+        );
         println!(
-            "Processing seed range #{}: {:?}",
-            seed_range_idx, seed_range
+            "Expected closest location for initial {} is {}",
+            SeedMode::Ranges,
+            generated.expected.1
         );
 
-        for seed in seed_range.clone() {
-            if let Some(loc) = almanac.try_map("seed", seed, "location") {
-                if loc < closest_location {
-                    closest_location = loc;
+        return Ok(());
+    }
 
-                    println!("Found closest location {} for seed {}.", loc, seed);
-                }
-            }
+    let almanac = match Almanac::from_reader(BufReader::new(File::open(INPUT)?)) {
+        Ok(almanac) => almanac,
+        Err(err) => {
+            let line_def = BufReader::new(File::open(INPUT)?)
+                .lines()
+                .nth(err.line() - 1)
+                .and_then(|line| line.ok())
+                .unwrap_or_default();
+            eprintln!("error: {}", err.annotate(&line_def));
+            process::exit(1);
+        }
+    };
+
+    let issues = almanac.validate();
+    for issue in issues.iter() {
+        println!("{}", issue);
+    }
+    let errors_count = issues
+        .iter()
+        .filter(|i| i.severity == Severity::Error)
+        .count();
+
+    // explaining and drawing the maps are most useful exactly when something is wrong, so they do not need a valid
+    // almanac
+    if let Some(Command::Explain { seed, len }) = cli.command {
+        for hop in almanac.trace("seed", checked_range(seed, len)?, "location")? {
+            println!("{}", hop);
         }
 
-        seed_range_idx += 1;
+        return Ok(());
     }
 
-    println!("Closest location for initial seeds is {}", closest_location);
+    if let Some(dot_path) = cli.dot {
+        fs::write(&dot_path, dot::to_dot(&almanac, cli.seed))?;
+        println!("Wrote maps graph to {}", dot_path.display());
+
+        return Ok(());
+    }
+
+    if errors_count > 0 {
+        Err(anyhow!(
+            "Almanac has {} error(s), refusing to solve it",
+            errors_count
+        ))?
+    }
+
+    for path in almanac.paths("seed", "location")? {
+        println!("Mapping seeds to locations through {}", path.join(" -> "));
+    }
+
+    if let Some(threads) = cli.brute_force {
+        return solve_brute_force(&almanac, threads);
+    }
+
+    for mode in [SeedMode::Individual, SeedMode::Ranges] {
+        let seeds = almanac.seed_ranges(mode)?;
+        let locations = almanac.try_map_ranges("seed", &seeds, "location")?;
+
+        println!(
+            "Initial {} map to {} location range(s): {:?}",
+            mode,
+            locations.len(),
+            locations
+        );
+
+        let (seed, closest_location) = almanac
+            .min_location(&seeds)
+            .ok_or(anyhow!("No initial seeds in almanac"))?;
+
+        println!(
+            "Closest location for initial {} is {}, where seed {} goes",
+            mode, closest_location, seed
+        );
+    }
 
     Ok(())
 }
@@ -1,39 +1,448 @@
-use std::collections::HashSet;
+use anyhow::{anyhow, Result};
+use std::cmp::{max, min, Reverse};
+use std::collections::{BTreeSet, BinaryHeap, HashSet};
+use std::fmt::Debug;
+use std::hash::Hash;
 use std::ops::Add;
 use std::{fmt::Display, ops::Range};
 
-#[derive(Debug)]
-pub struct Mapper {
+/// Type of the ids maps work with, wide enough for stress inputs with the `u128` feature.
+#[cfg(not(feature = "u128"))]
+pub type Id = u64;
+#[cfg(feature = "u128")]
+pub type Id = u128;
+
+/// Ordered integer-like type maps can work with, implemented for every primitive integer type.
+pub trait Key: Copy + Ord + Hash + Debug + Display + Send + Sync + 'static {
+    const ZERO: Self;
+    const ONE: Self;
+    const MIN: Self;
+    const MAX: Self;
+
+    fn checked_add(self, rhs: Self) -> Option<Self>;
+    fn wrapping_add(self, rhs: Self) -> Self;
+    fn wrapping_sub(self, rhs: Self) -> Self;
+    /// Returns the average of both keys, rounded down, without overflowing.
+    fn midpoint(self, rhs: Self) -> Self;
+}
+
+macro_rules! impl_key {
+    ($($t:ty),*) => {$(
+        impl Key for $t {
+            const ZERO: Self = 0;
+            const ONE: Self = 1;
+            const MIN: Self = <$t>::MIN;
+            const MAX: Self = <$t>::MAX;
+
+            fn checked_add(self, rhs: Self) -> Option<Self> {
+                <$t>::checked_add(self, rhs)
+            }
+
+            fn wrapping_add(self, rhs: Self) -> Self {
+                <$t>::wrapping_add(self, rhs)
+            }
+
+            fn wrapping_sub(self, rhs: Self) -> Self {
+                <$t>::wrapping_sub(self, rhs)
+            }
+
+            fn midpoint(self, rhs: Self) -> Self {
+                (self & rhs) + ((self ^ rhs) >> 1)
+            }
+        }
+    )*};
+}
+
+impl_key!(u8, u16, u32, u64, u128, usize, i8, i16, i32, i64, i128, isize);
+
+/// Moves `key` from the piece starting at `from` onto the piece starting at `to`.
+///
+/// Offsets between pieces may not fit into `K`, e.g. for signed keys far apart, but as the result does, wrapping
+/// arithmetic gets it right.
+pub(crate) fn shift<K: Key>(key: K, from: K, to: K) -> K {
+    to.wrapping_add(key.wrapping_sub(from))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RangeError<K = Id> {
+    /// Range starting at `start` with length `len` ends past `K::MAX`.
+    Overflow { start: K, len: K },
+    /// Range starting at `start` has a length below zero, only possible with signed keys.
+    Negative { start: K, len: K },
+}
+
+impl<K: Key> Display for RangeError<K> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RangeError::Overflow { start, len } => write!(
+                f,
+                "Range starting at {} with length {} goes past {}",
+                start,
+                len,
+                K::MAX
+            ),
+            RangeError::Negative { start, len } => {
+                write!(f, "Range starting at {} has negative length {}", start, len)
+            }
+        }
+    }
+}
+
+impl<K: Key> std::error::Error for RangeError<K> {}
+
+/// Builds the range of `len` ids starting at `start`, failing if its end does not fit into `K`.
+pub fn checked_range<K: Key>(start: K, len: K) -> Result<Range<K>, RangeError<K>> {
+    if len < K::ZERO {
+        return Err(RangeError::Negative { start, len });
+    }
+
+    match start.checked_add(len) {
+        Some(end) => Ok(start..end),
+        None => Err(RangeError::Overflow { start, len }),
+    }
+}
+
+/// Lays ranges out sorted and non-overlapping, where ranges overlap the one given first wins.
+///
+/// Returns the pieces covered by some range along with the index of the range winning there.
+pub(crate) fn lay_out<K: Key>(ranges: &[Range<K>]) -> Vec<(Range<K>, usize)> {
+    let mut by_start: Vec<usize> = (0..ranges.len())
+        .filter(|&idx| !ranges[idx].is_empty())
+        .collect();
+    by_start.sort_by_key(|&idx| ranges[idx].start);
+
+    let mut breakpoints: Vec<K> = by_start
+        .iter()
+        .flat_map(|&idx| [ranges[idx].start, ranges[idx].end])
+        .collect();
+    breakpoints.sort();
+    breakpoints.dedup();
+
+    // sweep through the pieces between breakpoints, keeping ranges covering the current piece by definition order
+    let mut pieces = vec![];
+    let mut covering = BinaryHeap::new();
+    let mut next = 0;
+    for w in breakpoints.windows(2) {
+        let (start, end) = (w[0], w[1]);
+
+        while next < by_start.len() && ranges[by_start[next]].start <= start {
+            covering.push(Reverse(by_start[next]));
+            next += 1;
+        }
+        while let Some(&Reverse(idx)) = covering.peek() {
+            if ranges[idx].end > start {
+                break;
+            }
+            covering.pop();
+        }
+
+        if let Some(&Reverse(idx)) = covering.peek() {
+            pieces.push((start..end, idx));
+        }
+    }
+
+    pieces
+}
+
+/// Map from `source` to `destination` keys shifting ranges of keys, and sending keys outside of them to themselves.
+///
+/// Maps are kept in a normal form: pieces are sorted by source start, do not overlap and adjacent pieces shifting
+/// keys by the same offset are merged. So two maps are equal exactly when they map every key the same way and
+/// between the same categories, and lookups take logarithmic time in the number of ranges.
+#[derive(Debug, PartialEq)]
+pub struct RangeMapper<K> {
     pub source: String,
     pub destination: String,
-    mappings: Vec<(Range<u64>, Range<u64>)>,
+    mappings: Vec<(Range<K>, Range<K>)>,
 }
 
-impl Mapper {
-    pub fn new(source: &str, destination: &str, maps: Vec<(u64, u64, u64)>) -> Self {
-        Mapper {
+/// Map between the ids of almanac categories.
+pub type Mapper = RangeMapper<Id>;
+
+impl<K: Key> RangeMapper<K> {
+    /// Builds a map from range definitions as `(destination start, source start, length)`, like in the almanac.
+    ///
+    /// Fails if the end of some source or destination range does not fit into `K`, or if some length is negative.
+    pub fn new(
+        source: &str,
+        destination: &str,
+        maps: Vec<(K, K, K)>,
+    ) -> Result<Self, RangeError<K>> {
+        let mut srcs = vec![];
+        for &(dst_start, src_start, len) in maps.iter() {
+            srcs.push(checked_range(src_start, len)?);
+            checked_range(dst_start, len)?;
+        }
+
+        let pieces = lay_out(&srcs).into_iter().map(|(src, idx)| {
+            let (dst_start, src_start, _len) = maps[idx];
+            let dst = shift(src.start, src_start, dst_start)..shift(src.end, src_start, dst_start);
+            (src, dst)
+        });
+
+        Ok(Self::from_pieces(source, destination, pieces))
+    }
+
+    /// Builds a map sending every key to itself.
+    pub fn identity(source: &str, destination: &str) -> Self {
+        Self::from_pieces(source, destination, [])
+    }
+
+    /// Builds a map from pieces sorted by source start and not overlapping.
+    fn from_pieces(
+        source: &str,
+        destination: &str,
+        pieces: impl IntoIterator<Item = (Range<K>, Range<K>)>,
+    ) -> Self {
+        RangeMapper {
             source: source.to_string(),
             destination: destination.to_string(),
-            mappings: maps
+            mappings: Self::normalize(pieces),
+        }
+    }
+
+    /// Brings sorted pieces to the form in which maps doing the same thing have equal `mappings`.
+    ///
+    /// Identity pieces are dropped, adjacent pieces with the same offset are merged, and gaps between the remaining
+    /// pieces are filled with identity pieces again, so that lookups can use binary search.
+    fn normalize(
+        pieces: impl IntoIterator<Item = (Range<K>, Range<K>)>,
+    ) -> Vec<(Range<K>, Range<K>)> {
+        let mut shifted: Vec<(Range<K>, Range<K>)> = vec![];
+        for (src, dst) in pieces {
+            if src.is_empty() || src.start == dst.start {
+                continue;
+            }
+
+            match shifted.last_mut() {
+                Some((prev_src, prev_dst))
+                    if prev_src.end == src.start && prev_dst.end == dst.start =>
+                {
+                    prev_src.end = src.end;
+                    prev_dst.end = dst.end;
+                }
+                _ => shifted.push((src, dst)),
+            }
+        }
+
+        let mut result: Vec<(Range<K>, Range<K>)> = vec![];
+        for (src, dst) in shifted {
+            if let Some(gap_start) = result.last().map(|(prev, _)| prev.end) {
+                if gap_start < src.start {
+                    result.push((gap_start..src.start, gap_start..src.start));
+                }
+            }
+            result.push((src, dst));
+        }
+
+        result
+    }
+
+    /// Writes a piece as range definitions `(destination start, source start, length)`.
+    ///
+    /// With signed keys pieces may be longer than `K::MAX`, those are cut into several definitions.
+    fn definitions(src: &Range<K>, dst: &Range<K>) -> Vec<(K, K, K)> {
+        let mut result = vec![];
+        let mut start = src.start;
+        while start < src.end {
+            let len = match src.end.wrapping_sub(start) {
+                len if len > K::ZERO => len,
+                _ => K::MAX,
+            };
+            result.push((shift(start, src.start, dst.start), start, len));
+            start = start.wrapping_add(len);
+        }
+
+        result
+    }
+
+    /// Lists the ranges of the map as `(destination start, source start, length)`, like in the almanac.
+    ///
+    /// Ranges are sorted by source start, and identity ranges are left out.
+    pub fn ranges(&self) -> Vec<(K, K, K)> {
+        self.mappings
+            .iter()
+            .filter(|(src, dst)| src.start != dst.start)
+            .flat_map(|(src, dst)| Self::definitions(src, dst))
+            .collect()
+    }
+
+    /// Finds the range mapping `src_id`, as `(destination start, source start, length)`.
+    ///
+    /// Returns `None` when the key is mapped to itself.
+    pub fn matching_range(&self, src_id: K) -> Option<(K, K, K)> {
+        let idx = self
+            .mappings
+            .partition_point(|(src, _dst)| src.end <= src_id);
+
+        match self.mappings.get(idx) {
+            Some((src, dst)) if src.contains(&src_id) && src.start != dst.start => {
+                Self::definitions(src, dst)
+                    .into_iter()
+                    .take_while(|&(_dst_start, src_start, _len)| src_start <= src_id)
+                    .last()
+            }
+            _ => None,
+        }
+    }
+
+    pub fn map(&self, src_id: K) -> K {
+        let idx = self
+            .mappings
+            .partition_point(|(src, _dst)| src.end <= src_id);
+
+        match self.mappings.get(idx) {
+            Some((src, dst)) if src.contains(&src_id) => shift(src_id, src.start, dst.start),
+            _ => src_id,
+        }
+    }
+
+    pub fn map_range(&self, src_range: Range<K>) -> Vec<Range<K>> {
+        self.split(src_range)
+            .into_iter()
+            .map(|(_src, dst)| dst)
+            .collect()
+    }
+
+    /// Computes the image of a set of ranges, merging the ranges it consists of.
+    pub fn map_ranges(&self, src_ranges: &[Range<K>]) -> Vec<Range<K>> {
+        merge_ranges(
+            src_ranges
                 .iter()
-                .map(|&(dst_start, src_start, len)| {
-                    (src_start..(src_start + len), dst_start..(dst_start + len))
-                })
+                .flat_map(|r| self.map_range(r.clone()))
                 .collect(),
+        )
+    }
+
+    /// Splits `src_range` into pieces on which the map is a plain shift, pairing each of them with its image.
+    pub fn split(&self, src_range: Range<K>) -> Vec<(Range<K>, Range<K>)> {
+        let mut result = vec![];
+        let mut curr = src_range.start;
+
+        let first = self
+            .mappings
+            .partition_point(|(src, _dst)| src.end <= src_range.start);
+        for (src, dst) in self.mappings[first..]
+            .iter()
+            .take_while(|(src, _dst)| src.start < src_range.end)
+        {
+            if curr < src.start {
+                result.push((curr..src.start, curr..src.start));
+                curr = src.start;
+            }
+
+            let end = min(src.end, src_range.end);
+            result.push((
+                curr..end,
+                shift(curr, src.start, dst.start)..shift(end, src.start, dst.start),
+            ));
+            curr = end;
         }
+
+        if curr < src_range.end {
+            result.push((curr..src_range.end, curr..src_range.end));
+        }
+
+        result
     }
 
-    pub fn map(&mut self, src_id: u64) -> u64 {
-        for mapping in self.mappings.iter_mut() {
-            if mapping.0.contains(&src_id) {
-                return mapping.1.start + (src_id - mapping.0.start);
+    /// Composes the map with `rhs` applied after it, so that `a.compose(b).map(x) == b.map(a.map(x))`.
+    ///
+    /// Returns `None` if `rhs` does not map from the destination of this map.
+    pub fn compose(&self, rhs: &RangeMapper<K>) -> Option<RangeMapper<K>> {
+        if self.destination != rhs.source {
+            return None;
+        }
+
+        // each piece of self is a plain shift, so it splits wherever rhs splits its image
+        let pieces = self.pieces().into_iter().flat_map(|(src, dst)| {
+            rhs.split(dst.clone()).into_iter().map(move |(mid, out)| {
+                (
+                    shift(mid.start, dst.start, src.start)..shift(mid.end, dst.start, src.start),
+                    out,
+                )
+            })
+        });
+
+        Some(Self::from_pieces(&self.source, &rhs.destination, pieces))
+    }
+
+    /// Restricts the map to `domain`, so that keys outside of it are mapped to themselves.
+    pub fn restrict(&self, domain: &[Range<K>]) -> RangeMapper<K> {
+        Self::from_pieces(
+            &self.source,
+            &self.destination,
+            merge_ranges(domain.to_vec())
+                .into_iter()
+                .flat_map(|r| self.split(r)),
+        )
+    }
+
+    /// Checks whether both maps send every key to the same key, whatever categories they map between.
+    pub fn is_equivalent(&self, other: &RangeMapper<K>) -> bool {
+        self.mappings == other.mappings
+    }
+
+    /// Splits the whole key space into pieces on which the map is a plain shift, identity pieces included.
+    fn pieces(&self) -> Vec<(Range<K>, Range<K>)> {
+        let (Some((first, _)), Some((last, _))) = (self.mappings.first(), self.mappings.last())
+        else {
+            return vec![(K::MIN..K::MAX, K::MIN..K::MAX)];
+        };
+
+        let mut result = vec![];
+        if first.start > K::MIN {
+            result.push((K::MIN..first.start, K::MIN..first.start));
+        }
+        result.extend(self.mappings.iter().cloned());
+        if last.end < K::MAX {
+            result.push((last.end..K::MAX, last.end..K::MAX));
+        }
+
+        result
+    }
+
+    /// Builds the reverse map from `destination` to `source` keys.
+    ///
+    /// Fails if the map is not injective, i.e. some destination keys are reachable from several source keys.
+    pub fn inverse(&self) -> Result<RangeMapper<K>> {
+        let mut pieces = self.pieces();
+        pieces.sort_by_key(|(_src, dst)| dst.start);
+
+        for w in pieces.windows(2) {
+            if let [(src1, dst1), (src2, dst2)] = w {
+                if dst2.start < dst1.end {
+                    Err(anyhow!(
+                        "Cannot invert {}: source ranges {:?} and {:?} both map onto {:?}",
+                        self,
+                        src1,
+                        src2,
+                        dst2.start..min(dst1.end, dst2.end)
+                    ))?
+                }
             }
         }
 
-        src_id
+        Ok(Self::from_pieces(
+            &self.destination,
+            &self.source,
+            pieces.into_iter().map(|(src, dst)| (dst, src)),
+        ))
     }
 
-    pub fn src_breakpoints(&self) -> HashSet<u64> {
+    /// Finds every source key mapped onto one of `dst_ids`.
+    pub fn preimage(&self, dst_ids: &BTreeSet<K>) -> BTreeSet<K> {
+        self.pieces()
+            .into_iter()
+            .flat_map(|(src, dst)| {
+                dst_ids
+                    .range(dst.clone())
+                    .map(move |&dst_id| shift(dst_id, dst.start, src.start))
+            })
+            .collect()
+    }
+
+    pub fn src_breakpoints(&self) -> HashSet<K> {
         let mut result = HashSet::new();
 
         for (src, _dst) in self.mappings.iter() {
@@ -44,7 +453,7 @@ impl Mapper {
         result
     }
 
-    pub fn dest_breakpoints(&self) -> HashSet<u64> {
+    pub fn dest_breakpoints(&self) -> HashSet<K> {
         let mut result = HashSet::new();
 
         for (_src, dst) in self.mappings.iter() {
@@ -56,50 +465,45 @@ impl Mapper {
     }
 }
 
-impl Display for Mapper {
+/// Sorts ranges and merges the overlapping or adjacent ones, dropping empty ranges.
+pub fn merge_ranges<K: Ord + Copy>(mut ranges: Vec<Range<K>>) -> Vec<Range<K>> {
+    ranges.retain(|r| !r.is_empty());
+    ranges.sort_by_key(|r| r.start);
+
+    let mut result: Vec<Range<K>> = vec![];
+    for r in ranges {
+        match result.last_mut() {
+            Some(last) if r.start <= last.end => last.end = max(last.end, r.end),
+            _ => result.push(r),
+        }
+    }
+
+    result
+}
+
+impl<K> Display for RangeMapper<K> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "{}-to-{} map", self.source, self.destination)
     }
 }
 
-impl Add for &mut Mapper {
-    type Output = Option<Mapper>;
+impl<K: Key> Add for &RangeMapper<K> {
+    type Output = Option<RangeMapper<K>>;
 
+    /// Composes two maps so that `(a + b).map(x) == b.map(a.map(x))` for every `x`.
     fn add(self, rhs: Self) -> Self::Output {
-        if self.destination != rhs.source {
-            None
-        } else {
-            let mut breakpoints: Vec<u64> = (&(&self.src_breakpoints() | &self.dest_breakpoints())
-                | &rhs.src_breakpoints())
-                .into_iter()
-                .collect();
-            breakpoints.sort();
-
-            let mut result_maps: Vec<(u64, u64, u64)> = vec![];
-            for w in breakpoints.windows(2) {
-                if let &[src_start, src_end] = w {
-                    let dst_start = rhs.map(self.map(src_start));
-                    let len = src_end - src_start;
-
-                    result_maps.push((dst_start, src_start, len));
-                } else {
-                    return None;
-                }
-            }
-
-            Some(Mapper::new(&self.source, &rhs.destination, result_maps))
-        }
+        self.compose(rhs)
     }
 }
 
 #[cfg(test)]
 mod test {
-    use super::Mapper;
-    use std::collections::HashSet;
+    use super::{merge_ranges, Id, Mapper, RangeError, RangeMapper};
+    use std::collections::{BTreeSet, HashSet};
 
     #[test]
     fn mapper_map_test() {
-        let mut mapper = Mapper::new("seed", "soil", vec![(50, 98, 2), (52, 50, 48)]);
+        let mapper = Mapper::new("seed", "soil", vec![(50, 98, 2), (52, 50, 48)]).unwrap();
 
         assert_eq!(mapper.map(98), 50);
         assert_eq!(mapper.map(99), 51);
@@ -107,19 +511,234 @@ mod test {
         assert_eq!(mapper.map(10), 10);
     }
 
+    #[test]
+    #[allow(clippy::single_range_in_vec_init)]
+    fn mapper_map_range_test() {
+        let mapper = Mapper::new("seed", "soil", vec![(50, 98, 2), (52, 50, 48)]).unwrap();
+
+        assert_eq!(mapper.map_range(10..20), vec![10..20]);
+        assert_eq!(mapper.map_range(60..70), vec![62..72]);
+        assert_eq!(mapper.map_range(45..55), vec![45..50, 52..57]);
+        assert_eq!(mapper.map_range(96..102), vec![98..100, 50..52, 100..102]);
+    }
+
+    #[test]
+    fn mapper_map_ranges_test() {
+        let mapper = Mapper::new("seed", "soil", vec![(50, 98, 2), (52, 50, 48)]).unwrap();
+
+        assert_eq!(mapper.map_ranges(&[45..55, 96..102]), vec![45..57, 98..102]);
+        assert_eq!(mapper.map_ranges(&[]), vec![]);
+    }
+
+    #[test]
+    fn merge_ranges_test() {
+        assert_eq!(
+            merge_ranges(vec![5..7, 1..3, 3..4, 6..10, 12..12]),
+            vec![1..4, 5..10]
+        );
+    }
+
+    #[test]
+    fn mapper_inverse_test() {
+        let mapper = Mapper::new("seed", "soil", vec![(50, 98, 2), (52, 50, 48)]).unwrap();
+        let inverse = mapper.inverse().unwrap();
+
+        assert_eq!(inverse.source, "soil");
+        assert_eq!(inverse.destination, "seed");
+        assert_eq!(inverse.map(50), 98);
+        assert_eq!(inverse.map(51), 99);
+        assert_eq!(inverse.map(55), 53);
+        assert_eq!(inverse.map(10), 10);
+        assert_eq!(inverse.map_ranges(&[45..52, 52..60]), vec![45..58, 98..100]);
+    }
+
+    #[test]
+    fn mapper_inverse_not_injective_test() {
+        let overlapping_dst = Mapper::new("seed", "soil", vec![(50, 98, 2), (51, 10, 2)]).unwrap();
+        assert!(overlapping_dst.inverse().is_err());
+
+        let overlapping_identity = Mapper::new("seed", "soil", vec![(50, 98, 2)]).unwrap();
+        assert!(overlapping_identity.inverse().is_err());
+    }
+
+    #[test]
+    fn mapper_matching_range_test() {
+        let mapper =
+            Mapper::new("seed", "soil", vec![(50, 98, 2), (52, 50, 48), (10, 10, 5)]).unwrap();
+
+        assert_eq!(mapper.matching_range(99), Some((50, 98, 2)));
+        assert_eq!(mapper.matching_range(50), Some((52, 50, 48)));
+        assert_eq!(mapper.matching_range(12), None);
+        assert_eq!(mapper.matching_range(100), None);
+    }
+
+    #[test]
+    fn mapper_ranges_test() {
+        let mapper = Mapper::new("seed", "soil", vec![(50, 98, 2), (52, 50, 48)]).unwrap();
+        assert_eq!(mapper.ranges(), vec![(52, 50, 48), (50, 98, 2)]);
+
+        let overlapping = Mapper::new(
+            "seed",
+            "soil",
+            vec![
+                (0, 10, 10),
+                (100, 5, 20),
+                (30, 30, 5),
+                (40, 40, 0),
+                (200, 50, 5),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            overlapping.ranges(),
+            vec![(100, 5, 5), (0, 10, 10), (115, 20, 5), (200, 50, 5)]
+        );
+        assert_eq!(
+            overlapping.mappings,
+            vec![
+                (5..10, 100..105),
+                (10..20, 0..10),
+                (20..25, 115..120),
+                (25..50, 25..50),
+                (50..55, 200..205)
+            ]
+        );
+    }
+
+    #[test]
+    fn mapper_overlap_first_wins_test() {
+        let mapper = Mapper::new("seed", "soil", vec![(0, 10, 10), (100, 5, 20)]).unwrap();
+
+        assert_eq!(mapper.map(7), 102);
+        assert_eq!(mapper.map(12), 2);
+        assert_eq!(mapper.map(19), 9);
+        assert_eq!(mapper.map(22), 117);
+        assert_eq!(mapper.map(25), 25);
+    }
+
+    #[test]
+    fn mapper_normalize_test() {
+        let split = Mapper::new(
+            "seed",
+            "soil",
+            vec![(100, 0, 5), (105, 5, 5), (20, 20, 5), (130, 30, 5)],
+        )
+        .unwrap();
+        let whole = Mapper::new("a", "b", vec![(100, 0, 10), (130, 30, 5)]).unwrap();
+
+        assert_eq!(
+            split.mappings,
+            vec![(0..10, 100..110), (10..30, 10..30), (30..35, 130..135)]
+        );
+        assert!(split.is_equivalent(&whole));
+        assert!(!split.is_equivalent(&Mapper::new("seed", "soil", vec![(100, 0, 10)]).unwrap()));
+    }
+
+    #[test]
+    fn mapper_compose_test() {
+        let m1 = Mapper::new("seed", "soil", vec![(50, 98, 2), (52, 50, 48)]).unwrap();
+        let m2 = Mapper::new(
+            "soil",
+            "fertilizer",
+            vec![(0, 15, 37), (37, 52, 2), (39, 0, 15)],
+        )
+        .unwrap();
+        let composed = m1.compose(&m2).unwrap();
+
+        assert_eq!(composed.source, "seed");
+        assert_eq!(composed.destination, "fertilizer");
+        for (seed, fertilizer) in [(79, 81), (14, 53), (55, 57), (13, 52), (98, 35)] {
+            assert_eq!(composed.map(seed), fertilizer);
+        }
+        assert!(m2.compose(&m1).is_none());
+
+        let inverse = m1.inverse().unwrap();
+        assert!(m1
+            .compose(&inverse)
+            .unwrap()
+            .is_equivalent(&Mapper::identity("seed", "seed")));
+    }
+
+    #[test]
+    fn mapper_restrict_test() {
+        let mapper = Mapper::new("seed", "soil", vec![(50, 98, 2), (52, 50, 48)]).unwrap();
+        let restricted = mapper.restrict(&[60..70, 99..120, 65..75]);
+
+        assert_eq!(restricted.ranges(), vec![(62, 60, 15), (51, 99, 1)]);
+        assert_eq!(restricted.map(59), 59);
+        assert_eq!(restricted.map(74), 76);
+        assert_eq!(restricted.map(98), 98);
+        assert!(restricted
+            .restrict(&[])
+            .is_equivalent(&Mapper::identity("seed", "soil")));
+    }
+
+    #[test]
+    fn mapper_new_overflow_test() {
+        assert_eq!(
+            Mapper::new("seed", "soil", vec![(0, Id::MAX - 1, 2)]),
+            Err(RangeError::Overflow {
+                start: Id::MAX - 1,
+                len: 2
+            })
+        );
+        assert_eq!(
+            Mapper::new("seed", "soil", vec![(50, 98, 2), (Id::MAX, 0, 1)]),
+            Err(RangeError::Overflow {
+                start: Id::MAX,
+                len: 1
+            })
+        );
+        assert!(Mapper::new("seed", "soil", vec![(0, Id::MAX - 1, 1)]).is_ok());
+    }
+
+    #[test]
+    fn mapper_signed_keys_test() {
+        let mapper = RangeMapper::<i64>::new("a", "b", vec![(-10, 5, 10), (20, -30, 5)]).unwrap();
+
+        assert_eq!(mapper.map(7), -8);
+        assert_eq!(mapper.map(-28), 22);
+        assert_eq!(mapper.map(-3), -3);
+        assert_eq!(
+            mapper.map_ranges(&[-40..-20, 0..10]),
+            vec![-40..-30, -25..-20, -10..-5, 0..5, 20..25]
+        );
+        assert_eq!(
+            RangeMapper::<i64>::new("a", "b", vec![(0, 3, -1)]),
+            Err(RangeError::Negative { start: 3, len: -1 })
+        );
+
+        // shifting almost every key up by one makes a piece longer than i8::MAX
+        let long = RangeMapper::<i8>::new("a", "b", vec![(-127, -128, 127), (0, -1, 127)]).unwrap();
+        assert_eq!(long.mappings, vec![(-128..126, -127..127)]);
+        assert_eq!(long.ranges(), vec![(-127, -128, 127), (0, -1, 127)]);
+        assert_eq!(long.matching_range(100), Some((0, -1, 127)));
+        assert_eq!(long.map(125), 126);
+    }
+
+    #[test]
+    fn mapper_preimage_test() {
+        let mapper = Mapper::new("seed", "soil", vec![(50, 98, 2), (52, 50, 48)]).unwrap();
+
+        assert_eq!(
+            mapper.preimage(&BTreeSet::from([10, 50, 51, 52, 99, 100])),
+            BTreeSet::from([10, 98, 99, 50, 97, 100])
+        );
+    }
+
     #[test]
     fn mapper_breakpoints_test() {
-        let mapper = Mapper::new("seed", "soil", vec![(50, 98, 2), (52, 50, 48)]);
+        let mapper = Mapper::new("seed", "soil", vec![(50, 98, 2), (52, 50, 48)]).unwrap();
 
         assert_eq!(mapper.src_breakpoints(), HashSet::from([50, 98, 100]));
     }
 
     #[test]
     fn mapper_add_mapper_src_dest_success_test() {
-        let mut m1 = Mapper::new("s", "d", vec![]);
-        let mut m2 = Mapper::new("d", "d2", vec![]);
+        let m1 = Mapper::identity("s", "d");
+        let m2 = Mapper::identity("d", "d2");
 
-        let res = &mut m1 + &mut m2;
+        let res = &m1 + &m2;
 
         assert!(
             matches!(res, Some(Mapper { source, destination, .. }) if source == m1.source && destination == m2.destination)
@@ -128,22 +747,22 @@ mod test {
 
     #[test]
     fn mapper_add_mapper_src_dest_fail_test() {
-        let mut m1 = Mapper::new("s", "d", vec![]);
-        let mut m2 = Mapper::new("s2", "d2", vec![]);
+        let m1 = Mapper::identity("s", "d");
+        let m2 = Mapper::identity("s2", "d2");
 
-        let res = &mut m1 + &mut m2;
+        let res = &m1 + &m2;
 
-        assert!(matches!(res, None));
+        assert!(res.is_none());
     }
 
     #[test]
     fn mapper_add_remap_test() {
-        let mut m1 = Mapper::new("s", "d", vec![(5, 3, 2), (3, 5, 2)]);
+        let m1 = Mapper::new("s", "d", vec![(5, 3, 2), (3, 5, 2)]).unwrap();
         println!("m1 breakpoints = {:?}", m1.src_breakpoints());
-        let mut m2 = Mapper::new("d", "d2", vec![(3, 2, 1), (2, 3, 1)]);
+        let m2 = Mapper::new("d", "d2", vec![(3, 2, 1), (2, 3, 1)]).unwrap();
         println!("m2 breakpoints = {:?}", m2.src_breakpoints());
 
-        let res = &mut m1 + &mut m2;
+        let res = &m1 + &m2;
 
         println!("res maps: {:?}", res);
 
@@ -151,4 +770,208 @@ mod test {
             matches!(res, Some(Mapper { mappings, .. }) if mappings == vec![(2..3, 3..4), (3..5, 5..7), (5..6, 2..3), (6..7, 4..5)])
         );
     }
+
+    mod properties {
+        use super::super::{Id, Mapper};
+        use proptest::collection::vec;
+        use proptest::prelude::*;
+        use std::ops::Range;
+
+        /// Range definitions in a small domain, so that ranges overlap, touch and leave gaps often.
+        fn small_range_defs() -> impl Strategy<Value = Vec<(Id, Id, Id)>> {
+            vec((0..200 as Id, 0..200 as Id, 0..50 as Id), 0..8)
+        }
+
+        /// Range definitions spread over the whole id space, like in real almanacs.
+        fn large_range_defs() -> impl Strategy<Value = Vec<(Id, Id, Id)>> {
+            vec((0..Id::MAX / 2, 0..Id::MAX / 2, 0..Id::MAX / 2), 0..8)
+        }
+
+        /// Range definitions that shuffle consecutive segments of the domain around, so the map is a bijection.
+        fn permutation_range_defs() -> impl Strategy<Value = Vec<(Id, Id, Id)>> {
+            (0..1000 as Id, vec(1..50 as Id, 1..8))
+                .prop_flat_map(|(base, lens)| {
+                    let order: Vec<usize> = (0..lens.len()).collect();
+                    (Just(base), Just(lens), Just(order).prop_shuffle())
+                })
+                .prop_map(|(base, lens, order)| {
+                    let mut src_start = base;
+                    let mut dst_starts = vec![0; lens.len()];
+                    let mut dst_start = base;
+                    for &idx in order.iter() {
+                        dst_starts[idx] = dst_start;
+                        dst_start += lens[idx];
+                    }
+
+                    lens.iter()
+                        .zip(dst_starts)
+                        .map(|(&len, dst_start)| {
+                            let def = (dst_start, src_start, len);
+                            src_start += len;
+                            def
+                        })
+                        .collect()
+                })
+        }
+
+        /// Points worth checking: every breakpoint of both maps, their neighbours and the domain edges.
+        fn probe_points(m1: &Mapper, m2: &Mapper, extra: &[Id]) -> Vec<Id> {
+            let mut points = vec![0, 1, Id::MAX - 1];
+            for b in m1
+                .src_breakpoints()
+                .into_iter()
+                .chain(m1.dest_breakpoints())
+                .chain(m2.src_breakpoints())
+                .chain(extra.iter().copied())
+            {
+                points.extend([b.saturating_sub(1), b, b.saturating_add(1).min(Id::MAX - 1)]);
+            }
+
+            points
+        }
+
+        fn check_composition(
+            defs1: Vec<(Id, Id, Id)>,
+            defs2: Vec<(Id, Id, Id)>,
+            extra: &[Id],
+        ) -> Result<(), TestCaseError> {
+            let m1 = Mapper::new("a", "b", defs1).unwrap();
+            let m2 = Mapper::new("b", "c", defs2).unwrap();
+            let composed = (&m1 + &m2).unwrap();
+
+            for x in probe_points(&m1, &m2, extra) {
+                let expected = m2.map(m1.map(x));
+                prop_assert_eq!(composed.map(x), expected, "composed map differs at {}", x);
+            }
+
+            Ok(())
+        }
+
+        /// Looks the id up the way the almanac defines it: the first range containing it wins.
+        fn linear_scan_map(defs: &[(Id, Id, Id)], src_id: Id) -> Id {
+            defs.iter()
+                .find(|&&(_dst_start, src_start, len)| {
+                    src_start <= src_id && src_id - src_start < len
+                })
+                .map_or(src_id, |&(dst_start, src_start, _len)| {
+                    dst_start + (src_id - src_start)
+                })
+        }
+
+        proptest! {
+            #[test]
+            fn map_matches_linear_scan_test(
+                defs in prop_oneof![small_range_defs(), large_range_defs()],
+                extra in vec(0..300 as Id, 0..32),
+            ) {
+                let mapper = Mapper::new("a", "b", defs.clone()).unwrap();
+                let identity = Mapper::identity("b", "c");
+
+                for x in probe_points(&mapper, &identity, &extra) {
+                    prop_assert_eq!(mapper.map(x), linear_scan_map(&defs, x), "map differs at {}", x);
+                }
+                for &(_dst_start, src_start, len) in defs.iter() {
+                    let src_end = src_start + len;
+                    for x in [src_start.saturating_sub(1), src_start, src_end.saturating_sub(1), src_end] {
+                        prop_assert_eq!(mapper.map(x), linear_scan_map(&defs, x), "map differs at {}", x);
+                    }
+                }
+            }
+
+            #[test]
+            fn add_matches_sequential_map_small_test(
+                defs1 in small_range_defs(),
+                defs2 in small_range_defs(),
+                extra in vec(0..300 as Id, 0..32),
+            ) {
+                check_composition(defs1, defs2, &extra)?;
+            }
+
+            #[test]
+            fn add_matches_sequential_map_large_test(
+                defs1 in large_range_defs(),
+                defs2 in large_range_defs(),
+                extra in vec(any::<Id>(), 0..32),
+            ) {
+                check_composition(defs1, defs2, &extra)?;
+            }
+
+            #[test]
+            fn inverse_undoes_map_test(
+                defs in prop_oneof![permutation_range_defs(), small_range_defs()],
+                extra in vec(0..2000 as Id, 0..32),
+            ) {
+                let mapper = Mapper::new("a", "b", defs).unwrap();
+                let identity = Mapper::identity("b", "c");
+
+                if let Ok(inverse) = mapper.inverse() {
+                    for x in probe_points(&mapper, &identity, &extra) {
+                        let y = mapper.map(x);
+                        prop_assert_eq!(inverse.map(y), x, "inverse differs at {}", y);
+                    }
+                } else {
+                    let mut images = std::collections::HashMap::new();
+                    let collision = probe_points(&mapper, &identity, &extra)
+                        .into_iter()
+                        .chain(0..2000)
+                        .any(|x| images.insert(mapper.map(x), x).is_some_and(|prev| prev != x));
+                    prop_assert!(collision, "map is injective but inverse failed");
+                }
+            }
+
+            #[test]
+            fn add_is_associative_test(
+                defs1 in small_range_defs(),
+                defs2 in small_range_defs(),
+                defs3 in small_range_defs(),
+                extra in vec(0..300 as Id, 0..32),
+            ) {
+                let m1 = Mapper::new("a", "b", defs1).unwrap();
+                let m2 = Mapper::new("b", "c", defs2).unwrap();
+                let m3 = Mapper::new("c", "d", defs3).unwrap();
+
+                let left = (&(&m1 + &m2).unwrap() + &m3).unwrap();
+                let right = (&m1 + &(&m2 + &m3).unwrap()).unwrap();
+
+                for x in probe_points(&m1, &m2, &extra) {
+                    prop_assert_eq!(left.map(x), right.map(x), "compositions differ at {}", x);
+                }
+                prop_assert!(left.is_equivalent(&right), "{:?} and {:?} are not normalized alike", left, right);
+            }
+
+            #[test]
+            fn restrict_matches_map_test(
+                defs in small_range_defs(),
+                domain in vec((0..300 as Id, 0..50 as Id), 0..4),
+                extra in vec(0..300 as Id, 0..32),
+            ) {
+                let mapper = Mapper::new("a", "b", defs).unwrap();
+                let domain: Vec<Range<Id>> = domain.into_iter().map(|(start, len)| start..(start + len)).collect();
+                let restricted = mapper.restrict(&domain);
+
+                for x in probe_points(&mapper, &restricted, &extra) {
+                    let expected = if domain.iter().any(|r| r.contains(&x)) { mapper.map(x) } else { x };
+                    prop_assert_eq!(restricted.map(x), expected, "restricted map differs at {}", x);
+                }
+                prop_assert_eq!(restricted.map_ranges(&domain), mapper.map_ranges(&domain));
+            }
+
+            #[test]
+            fn add_produces_sorted_disjoint_ranges_test(
+                defs1 in small_range_defs(),
+                defs2 in small_range_defs(),
+            ) {
+                let m1 = Mapper::new("a", "b", defs1).unwrap();
+                let m2 = Mapper::new("b", "c", defs2).unwrap();
+                let composed = (&m1 + &m2).unwrap();
+
+                for w in composed.mappings.windows(2) {
+                    prop_assert_eq!(w[0].0.end, w[1].0.start, "ranges {:?} are not sorted and contiguous", w);
+                }
+                for (src, dst) in composed.mappings.first().iter().chain(composed.mappings.last().iter()) {
+                    prop_assert_ne!(src.start, dst.start, "identity range {:?} is kept at the edge", src);
+                }
+            }
+        }
+    }
 }
@@ -0,0 +1,323 @@
+use crate::mapper::{lay_out, merge_ranges, Key, RangeMapper};
+use std::collections::BTreeSet;
+use std::fmt::{Debug, Display};
+use std::ops::Range;
+use std::sync::Arc;
+
+/// Function moving the keys of a piece, either non-decreasing or non-increasing on it.
+pub type Monotone<K> = Arc<dyn Fn(K) -> K + Send + Sync>;
+
+/// Function adding `delta` to keys, wrapping around so that unsigned keys can be moved down too.
+pub fn offset<K: Key>(delta: K) -> Monotone<K> {
+    Arc::new(move |key: K| key.wrapping_add(delta))
+}
+
+/// Map from `source` to `destination` keys applying a monotone function on each of its ranges, and sending keys
+/// outside of them to themselves.
+///
+/// Generalizes `RangeMapper`, whose ranges are shifted by an offset, to functions like scaling or reversing. Each
+/// function has to map its range into `K` and be monotone on it, which is what makes splitting ranges at the
+/// breakpoints of another map a binary search.
+#[derive(Clone)]
+pub struct MonotoneMapper<K> {
+    pub source: String,
+    pub destination: String,
+    /// Sorted and non-overlapping, `None` is identity.
+    pieces: Vec<(Range<K>, Option<Monotone<K>>)>,
+}
+
+impl<K: Key> MonotoneMapper<K> {
+    /// Builds a map from ranges with their functions, where ranges overlap the one given first wins.
+    pub fn new(source: &str, destination: &str, maps: Vec<(Range<K>, Monotone<K>)>) -> Self {
+        let ranges: Vec<Range<K>> = maps.iter().map(|(range, _f)| range.clone()).collect();
+
+        MonotoneMapper {
+            source: source.to_string(),
+            destination: destination.to_string(),
+            pieces: lay_out(&ranges)
+                .into_iter()
+                .map(|(range, idx)| (range, Some(maps[idx].1.clone())))
+                .collect(),
+        }
+    }
+
+    fn function_at(&self, key: K) -> Option<&Monotone<K>> {
+        let idx = self.pieces.partition_point(|(range, _f)| range.end <= key);
+
+        match self.pieces.get(idx) {
+            Some((range, f)) if range.contains(&key) => f.as_ref(),
+            _ => None,
+        }
+    }
+
+    pub fn map(&self, key: K) -> K {
+        self.function_at(key).map_or(key, |f| f(key))
+    }
+
+    /// Splits `range` into pieces on which a single function applies, identity pieces included.
+    fn split(&self, range: Range<K>) -> Vec<(Range<K>, Option<Monotone<K>>)> {
+        let mut result = vec![];
+        let mut curr = range.start;
+
+        let first = self
+            .pieces
+            .partition_point(|(piece, _f)| piece.end <= range.start);
+        for (piece, f) in self.pieces[first..]
+            .iter()
+            .take_while(|(piece, _f)| piece.start < range.end)
+        {
+            if curr < piece.start {
+                result.push((curr..piece.start, None));
+                curr = piece.start;
+            }
+
+            let end = piece.end.min(range.end);
+            result.push((curr..end, f.clone()));
+            curr = end;
+        }
+
+        if curr < range.end {
+            result.push((curr..range.end, None));
+        }
+
+        result
+    }
+
+    /// Computes ranges covering the image of `range`.
+    ///
+    /// Functions other than shifts may leave holes in the image of a piece, the smallest range containing it is
+    /// used instead, so the lowest and highest keys of the result are still exact.
+    pub fn map_range(&self, range: Range<K>) -> Vec<Range<K>> {
+        self.split(range)
+            .into_iter()
+            .map(|(piece, f)| match f {
+                Some(f) => {
+                    let (first, last) = (f(piece.start), f(piece.end.wrapping_sub(K::ONE)));
+                    let (low, high) = (first.min(last), first.max(last));
+                    low..high.checked_add(K::ONE).unwrap_or(K::MAX)
+                }
+                None => piece,
+            })
+            .collect()
+    }
+
+    /// Computes ranges covering the image of a set of ranges, merging them.
+    pub fn map_ranges(&self, ranges: &[Range<K>]) -> Vec<Range<K>> {
+        merge_ranges(
+            ranges
+                .iter()
+                .flat_map(|r| self.map_range(r.clone()))
+                .collect(),
+        )
+    }
+
+    /// Composes the map with `rhs` applied after it, so that `a.compose(b).map(x) == b.map(a.map(x))`.
+    ///
+    /// Returns `None` if `rhs` does not map from the destination of this map.
+    pub fn compose(&self, rhs: &MonotoneMapper<K>) -> Option<MonotoneMapper<K>> {
+        if self.destination != rhs.source {
+            return None;
+        }
+
+        let breakpoints: BTreeSet<K> = rhs
+            .pieces
+            .iter()
+            .flat_map(|(range, _f)| [range.start, range.end])
+            .collect();
+
+        let mut pieces = vec![];
+        for (range, f) in self.split(K::MIN..K::MAX) {
+            let cuts: Vec<K> = match &f {
+                // a monotone function crosses every breakpoint of rhs at most once, so where it does is a binary search
+                Some(f) => {
+                    let (first, last) = (f(range.start), f(range.end.wrapping_sub(K::ONE)));
+                    let increasing = first <= last;
+                    let (low, high) = (first.min(last), first.max(last));
+
+                    breakpoints
+                        .range(low..=high)
+                        .map(|&b| partition_point(range.clone(), |key| (f(key) >= b) == increasing))
+                        .collect()
+                }
+                None => breakpoints.range(range.clone()).copied().collect(),
+            };
+
+            let mut bounds: Vec<K> = [range.start, range.end]
+                .into_iter()
+                .chain(cuts)
+                .filter(|key| range.start <= *key && *key <= range.end)
+                .collect();
+            bounds.sort();
+            bounds.dedup();
+
+            for w in bounds.windows(2) {
+                let mid = f.as_ref().map_or(w[0], |f| f(w[0]));
+                let composed = match (&f, rhs.function_at(mid)) {
+                    (None, None) => continue,
+                    (Some(f), None) => f.clone(),
+                    (None, Some(g)) => g.clone(),
+                    (Some(f), Some(g)) => {
+                        let (f, g) = (f.clone(), g.clone());
+                        Arc::new(move |key: K| g(f(key))) as Monotone<K>
+                    }
+                };
+                pieces.push((w[0]..w[1], Some(composed)));
+            }
+        }
+
+        Some(MonotoneMapper {
+            source: self.source.clone(),
+            destination: rhs.destination.clone(),
+            pieces,
+        })
+    }
+}
+
+/// Finds the first key of `range` satisfying `pred`, or the end of `range` if there is none.
+///
+/// `pred` has to be false for some keys at the start of the range and true for the rest.
+fn partition_point<K: Key>(range: Range<K>, pred: impl Fn(K) -> bool) -> K {
+    let (mut low, mut high) = (range.start, range.end);
+    while low < high {
+        let mid = low.midpoint(high);
+        if pred(mid) {
+            high = mid;
+        } else {
+            low = mid.wrapping_add(K::ONE);
+        }
+    }
+
+    low
+}
+
+impl<K: Key> From<&RangeMapper<K>> for MonotoneMapper<K> {
+    fn from(mapper: &RangeMapper<K>) -> Self {
+        MonotoneMapper {
+            source: mapper.source.clone(),
+            destination: mapper.destination.clone(),
+            pieces: mapper
+                .split(K::MIN..K::MAX)
+                .into_iter()
+                .filter(|(src, dst)| src.start != dst.start)
+                .map(|(src, dst)| {
+                    let delta = dst.start.wrapping_sub(src.start);
+                    (src, Some(offset(delta)))
+                })
+                .collect(),
+        }
+    }
+}
+
+impl<K: Debug> Debug for MonotoneMapper<K> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MonotoneMapper")
+            .field("source", &self.source)
+            .field("destination", &self.destination)
+            .field(
+                "ranges",
+                &self
+                    .pieces
+                    .iter()
+                    .filter(|(_range, f)| f.is_some())
+                    .map(|(range, _f)| range)
+                    .collect::<Vec<&Range<K>>>(),
+            )
+            .finish()
+    }
+}
+
+impl<K> Display for MonotoneMapper<K> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-to-{} map", self.source, self.destination)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{offset, Monotone, MonotoneMapper};
+    use crate::mapper::{Key, Mapper, RangeMapper};
+    use std::ops::Mul;
+    use std::sync::Arc;
+
+    fn scale<K: Key + Mul<Output = K>>(factor: K) -> Monotone<K> {
+        Arc::new(move |key| key * factor)
+    }
+
+    #[test]
+    #[allow(clippy::single_range_in_vec_init)]
+    fn monotone_map_test() {
+        let mapper = MonotoneMapper::new(
+            "a",
+            "b",
+            vec![
+                (0..10, scale(2)),
+                (20..30, Arc::new(|key: i64| 129 - key) as Monotone<i64>),
+                (5..25, offset(-3)),
+            ],
+        );
+
+        assert_eq!(mapper.map(-5), -5);
+        assert_eq!(mapper.map(4), 8);
+        assert_eq!(mapper.map(7), 14);
+        assert_eq!(mapper.map(12), 9);
+        assert_eq!(mapper.map(22), 107);
+        assert_eq!(mapper.map(30), 30);
+
+        assert_eq!(mapper.map_range(-10..5), vec![-10..0, 0..9]);
+        assert_eq!(mapper.map_range(18..22), vec![15..17, 108..110]);
+        assert_eq!(mapper.map_ranges(&[8..12]), vec![7..9, 16..19]);
+    }
+
+    #[test]
+    fn monotone_compose_test() {
+        let m1 = MonotoneMapper::new(
+            "a",
+            "b",
+            vec![
+                (-20..0, Arc::new(|key: i64| -key) as Monotone<i64>),
+                (0..10, scale(3)),
+                (10..15, offset(-100)),
+            ],
+        );
+        let m2 = MonotoneMapper::new(
+            "b",
+            "c",
+            vec![
+                (5..12, Arc::new(|key: i64| 40 - 2 * key) as Monotone<i64>),
+                (-90..-85, offset(7)),
+                (20..25, scale(-1)),
+            ],
+        );
+        let composed = m1.compose(&m2).unwrap();
+
+        assert_eq!(composed.source, "a");
+        assert_eq!(composed.destination, "c");
+        for key in -120..120 {
+            assert_eq!(composed.map(key), m2.map(m1.map(key)), "differs at {}", key);
+        }
+        assert!(m2.compose(&m1).is_none());
+    }
+
+    #[test]
+    fn monotone_from_range_mapper_test() {
+        let mapper = Mapper::new("seed", "soil", vec![(50, 98, 2), (52, 50, 48)]).unwrap();
+        let monotone = MonotoneMapper::from(&mapper);
+
+        for seed in 0..120 {
+            assert_eq!(monotone.map(seed), mapper.map(seed));
+        }
+        assert_eq!(
+            monotone.map_ranges(&[45..55, 96..102]),
+            vec![45..57, 98..102]
+        );
+
+        let signed = RangeMapper::<i32>::new("a", "b", vec![(-10, 5, 10)]).unwrap();
+        let composed = MonotoneMapper::from(&signed)
+            .compose(&MonotoneMapper::new("b", "c", vec![(-8..-4, scale(10))]))
+            .unwrap();
+        assert_eq!(composed.map(6), -9);
+        assert_eq!(composed.map(7), -80);
+        assert_eq!(composed.map(10), -50);
+        assert_eq!(composed.map(12), -3);
+    }
+}
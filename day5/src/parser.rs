@@ -0,0 +1,484 @@
+use crate::mapper::{Id, Mapper};
+use crate::validation::{MapDefinition, RangeDefinition};
+use std::cmp::max;
+use std::fmt::Display;
+use std::io::BufRead;
+
+/// Reason the almanac text could not be parsed, pointing at the offending token.
+///
+/// Lines and columns start at 1; the token is empty when something is missing at the end of a line.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AlmanacParseError {
+    /// Almanac does not start with the `seeds:` line.
+    MissingSeeds {
+        line: usize,
+        column: usize,
+        token: String,
+    },
+    /// Seed or range element is not a number fitting into `Id`.
+    InvalidNumber {
+        line: usize,
+        column: usize,
+        token: String,
+    },
+    /// Map does not start with a name line like `seed-to-soil map:`.
+    InvalidMapName {
+        line: usize,
+        column: usize,
+        token: String,
+    },
+    /// Range line does not consist of exactly three numbers.
+    InvalidRange {
+        line: usize,
+        column: usize,
+        token: String,
+    },
+    /// Line could not be read at all, e.g. because it is not valid UTF-8.
+    Unreadable { line: usize, message: String },
+}
+
+impl AlmanacParseError {
+    pub fn line(&self) -> usize {
+        match self {
+            AlmanacParseError::MissingSeeds { line, .. }
+            | AlmanacParseError::InvalidNumber { line, .. }
+            | AlmanacParseError::InvalidMapName { line, .. }
+            | AlmanacParseError::InvalidRange { line, .. }
+            | AlmanacParseError::Unreadable { line, .. } => *line,
+        }
+    }
+
+    pub fn column(&self) -> usize {
+        match self {
+            AlmanacParseError::MissingSeeds { column, .. }
+            | AlmanacParseError::InvalidNumber { column, .. }
+            | AlmanacParseError::InvalidMapName { column, .. }
+            | AlmanacParseError::InvalidRange { column, .. } => *column,
+            AlmanacParseError::Unreadable { .. } => 1,
+        }
+    }
+
+    pub fn token(&self) -> &str {
+        match self {
+            AlmanacParseError::MissingSeeds { token, .. }
+            | AlmanacParseError::InvalidNumber { token, .. }
+            | AlmanacParseError::InvalidMapName { token, .. }
+            | AlmanacParseError::InvalidRange { token, .. } => token,
+            AlmanacParseError::Unreadable { .. } => "",
+        }
+    }
+
+    /// Describes what was expected in place of the token.
+    pub fn expected(&self) -> String {
+        match self {
+            AlmanacParseError::MissingSeeds { .. } => "seeds: <seed> <seed> ...".to_string(),
+            AlmanacParseError::InvalidNumber { .. } => format!("number from 0 to {}", Id::MAX),
+            AlmanacParseError::InvalidMapName { .. } => {
+                "<source>-to-<destination> map:".to_string()
+            }
+            AlmanacParseError::InvalidRange { .. } => {
+                "<destination start> <source start> <length>".to_string()
+            }
+            AlmanacParseError::Unreadable { .. } => "UTF-8 text".to_string(),
+        }
+    }
+
+    /// Renders the error followed by `line_def`, the offending line, with the token marked by carets.
+    pub fn annotate(&self, line_def: &str) -> String {
+        if let AlmanacParseError::Unreadable { .. } = self {
+            return self.to_string();
+        }
+
+        let number = self.line().to_string();
+        let margin = " ".repeat(number.len());
+
+        format!(
+            "{}\n{} |\n{} | {}\n{} | {}{}",
+            self,
+            margin,
+            number,
+            line_def,
+            margin,
+            " ".repeat(self.column() - 1),
+            "^".repeat(max(1, self.token().chars().count()))
+        )
+    }
+}
+
+impl Display for AlmanacParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let AlmanacParseError::Unreadable { line, message } = self {
+            return write!(f, "line {}: cannot read almanac: {}", line, message);
+        }
+
+        write!(
+            f,
+            "line {}, column {}: expected {}, found ",
+            self.line(),
+            self.column(),
+            self.expected()
+        )?;
+
+        match self.token() {
+            "" => write!(f, "end of line"),
+            token => write!(f, "'{}'", token),
+        }
+    }
+}
+
+impl std::error::Error for AlmanacParseError {}
+
+/// Reads an almanac line by line, yielding its maps one at a time without keeping the text around.
+///
+/// Lines may end with `\n` or `\r\n`. Everything after `#` is a comment, and lines holding nothing but a comment
+/// are skipped altogether. Lines holding only whitespace separate sections. The `seeds:` line comes first and its
+/// numbers may continue on the following lines. Every map starts with a name line like `seed-to-soil map:`, either
+/// after a separator or right after the last range of the previous map.
+pub struct AlmanacReader<R> {
+    reader: R,
+    buf: String,
+    line: usize,
+    seeds: Vec<Id>,
+    next_map: Option<(usize, String)>,
+}
+
+impl<R: BufRead> AlmanacReader<R> {
+    /// Starts reading the almanac, consuming the seeds.
+    pub fn new(reader: R) -> Result<Self, AlmanacParseError> {
+        let mut almanac_reader = AlmanacReader {
+            reader,
+            buf: String::new(),
+            line: 0,
+            seeds: vec![],
+            next_map: None,
+        };
+        almanac_reader.read_seeds()?;
+
+        Ok(almanac_reader)
+    }
+
+    pub fn seeds(&self) -> &[Id] {
+        &self.seeds
+    }
+
+    /// Reads the next line that is not just a comment, with its line ending and comment stripped, or `None` at the
+    /// end of the almanac.
+    fn read_line(&mut self) -> Result<Option<String>, AlmanacParseError> {
+        loop {
+            self.buf.clear();
+            let read = self.reader.read_line(&mut self.buf).map_err(|e| {
+                AlmanacParseError::Unreadable {
+                    line: self.line + 1,
+                    message: e.to_string(),
+                }
+            })?;
+            if read == 0 {
+                return Ok(None);
+            }
+            self.line += 1;
+
+            let line_def = self.buf.trim_end_matches(['\n', '\r']);
+            match line_def.find('#') {
+                Some(idx) if line_def[..idx].trim().is_empty() => continue,
+                Some(idx) => return Ok(Some(line_def[..idx].to_string())),
+                None => return Ok(Some(line_def.to_string())),
+            }
+        }
+    }
+
+    fn read_seeds(&mut self) -> Result<(), AlmanacParseError> {
+        let seeds_def = loop {
+            match self.read_line()? {
+                Some(line_def) if line_def.trim().is_empty() => continue,
+                Some(line_def) => break line_def,
+                None => Err(AlmanacParseError::MissingSeeds {
+                    line: max(1, self.line),
+                    column: 1,
+                    token: String::new(),
+                })?,
+            }
+        };
+
+        let prefix_start = seeds_def.len() - seeds_def.trim_start().len();
+        if !seeds_def[prefix_start..].starts_with("seeds:") {
+            let (column, token) = tokens(&seeds_def, 0)[0];
+            Err(AlmanacParseError::MissingSeeds {
+                line: self.line,
+                column,
+                token: token.to_string(),
+            })?
+        }
+        for token in tokens(&seeds_def, prefix_start + "seeds:".len()) {
+            self.seeds.push(parse_id(self.line, token)?);
+        }
+
+        while let Some(line_def) = self.read_line()? {
+            if line_def.trim().is_empty() {
+                break;
+            }
+            if is_map_name(&line_def) {
+                self.next_map = Some((self.line, line_def));
+                break;
+            }
+
+            for token in tokens(&line_def, 0) {
+                self.seeds.push(parse_id(self.line, token)?);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn read_map(&mut self) -> Result<Option<(Mapper, MapDefinition)>, AlmanacParseError> {
+        let (map_line, name_def) = match self.next_map.take() {
+            Some(next_map) => next_map,
+            None => loop {
+                match self.read_line()? {
+                    Some(line_def) if line_def.trim().is_empty() => continue,
+                    Some(line_def) => break (self.line, line_def),
+                    None => return Ok(None),
+                }
+            },
+        };
+
+        let name = name_def.trim();
+        let (src, dest) = match name
+            .strip_suffix(" map:")
+            .map(|name| name.split('-').collect::<Vec<&str>>())
+            .as_deref()
+        {
+            Some(&[s, _, d]) if !s.is_empty() && !d.is_empty() => (s.to_string(), d.to_string()),
+            _ => Err(AlmanacParseError::InvalidMapName {
+                line: map_line,
+                column: tokens(&name_def, 0)[0].0,
+                token: name.to_string(),
+            })?,
+        };
+
+        let mut range_definitions: Vec<RangeDefinition> = vec![];
+        while let Some(range_def) = self.read_line()? {
+            if range_def.trim().is_empty() {
+                break;
+            }
+            if is_map_name(&range_def) {
+                self.next_map = Some((self.line, range_def));
+                break;
+            }
+
+            let range_tokens = tokens(&range_def, 0);
+            let [dst_start, src_start, len] = match range_tokens[..] {
+                [dst_start, src_start, len] => [dst_start, src_start, len],
+                [_, _, _, extra, ..] => Err(AlmanacParseError::InvalidRange {
+                    line: self.line,
+                    column: extra.0,
+                    token: extra.1.to_string(),
+                })?,
+                _ => Err(AlmanacParseError::InvalidRange {
+                    line: self.line,
+                    column: range_def.trim_end().chars().count() + 1,
+                    token: String::new(),
+                })?,
+            };
+
+            range_definitions.push(RangeDefinition {
+                line: Some(self.line),
+                dst_start: parse_id(self.line, dst_start)?,
+                src_start: parse_id(self.line, src_start)?,
+                len: parse_id(self.line, len)?,
+            });
+        }
+
+        // ranges that do not fit into ids are left out of the map and reported by validation
+        let range_maps = range_definitions
+            .iter()
+            .filter(|r| r.src().is_some() && r.dst().is_some())
+            .map(|r| (r.dst_start, r.src_start, r.len))
+            .collect();
+
+        Ok(Some((
+            Mapper::new(&src, &dest, range_maps).expect("overflowing ranges are filtered out"),
+            MapDefinition {
+                line: Some(map_line),
+                source: src,
+                destination: dest,
+                ranges: range_definitions,
+            },
+        )))
+    }
+}
+
+impl<R: BufRead> Iterator for AlmanacReader<R> {
+    type Item = Result<(Mapper, MapDefinition), AlmanacParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_map().transpose()
+    }
+}
+
+/// Tells whether the line is meant to name a map rather than to hold numbers, even if the name is misspelled.
+fn is_map_name(line_def: &str) -> bool {
+    !line_def
+        .trim_start()
+        .starts_with(|c: char| c.is_ascii_digit())
+}
+
+/// Splits `line` into whitespace separated tokens starting at byte `from`, along with their 1-based columns.
+fn tokens(line: &str, from: usize) -> Vec<(usize, &str)> {
+    let column = |idx: usize| line[..idx].chars().count() + 1;
+
+    let mut result = vec![];
+    let mut token_start = None;
+    for (idx, c) in line[from..].char_indices().map(|(idx, c)| (from + idx, c)) {
+        match (token_start, c.is_whitespace()) {
+            (None, false) => token_start = Some(idx),
+            (Some(start), true) => {
+                result.push((column(start), &line[start..idx]));
+                token_start = None;
+            }
+            _ => {}
+        }
+    }
+    if let Some(start) = token_start {
+        result.push((column(start), &line[start..]));
+    }
+
+    result
+}
+
+fn parse_id(line: usize, (column, token): (usize, &str)) -> Result<Id, AlmanacParseError> {
+    token
+        .parse::<Id>()
+        .map_err(|_| AlmanacParseError::InvalidNumber {
+            line,
+            column,
+            token: token.to_string(),
+        })
+}
+
+#[cfg(test)]
+mod test {
+    use super::{AlmanacParseError, AlmanacReader};
+    use crate::mapper::{Id, Mapper};
+    use std::io::{BufReader, Read};
+
+    #[test]
+    fn reader_crlf_comments_test() {
+        let almanac_def = concat!(
+            "# generated almanac\r\n",
+            "seeds: 79 14 # first pair\r\n",
+            "  55 13\r\n",
+            " \t \r\n",
+            "seed-to-soil map:\r\n",
+            "50 98 2\r\n",
+            "# 0 0 0\r\n",
+            "52 50 48 # last range\r\n",
+            "soil-to-fertilizer map:\r\n",
+            "0 15 37\r\n",
+            "   \r\n",
+            "\r\n",
+            "fertilizer-to-water map:\r\n",
+        );
+        let mut reader = AlmanacReader::new(almanac_def.as_bytes()).unwrap();
+
+        assert_eq!(reader.seeds(), &[79, 14, 55, 13]);
+
+        let (mapper, definition) = reader.next().unwrap().unwrap();
+        assert_eq!(
+            mapper,
+            Mapper::new("seed", "soil", vec![(50, 98, 2), (52, 50, 48)]).unwrap()
+        );
+        assert_eq!(definition.line, Some(5));
+        assert_eq!(
+            definition
+                .ranges
+                .iter()
+                .map(|r| r.line)
+                .collect::<Vec<Option<usize>>>(),
+            vec![Some(6), Some(8)]
+        );
+
+        let (mapper, definition) = reader.next().unwrap().unwrap();
+        assert_eq!(
+            mapper,
+            Mapper::new("soil", "fertilizer", vec![(0, 15, 37)]).unwrap()
+        );
+        assert_eq!(definition.line, Some(9));
+
+        let (mapper, definition) = reader.next().unwrap().unwrap();
+        assert_eq!(mapper, Mapper::identity("fertilizer", "water"));
+        assert_eq!(definition.line, Some(13));
+
+        assert!(reader.next().is_none());
+    }
+
+    /// Reader handing out almanac text one line per read and counting them, to check maps come before the end.
+    struct LineByLine<'a> {
+        lines: Vec<&'a str>,
+        read: usize,
+    }
+
+    impl Read for LineByLine<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            match self.lines.get(self.read) {
+                Some(line) => {
+                    buf[..line.len()].copy_from_slice(line.as_bytes());
+                    self.read += 1;
+                    Ok(line.len())
+                }
+                None => Ok(0),
+            }
+        }
+    }
+
+    #[test]
+    fn reader_is_incremental_test() {
+        let source = LineByLine {
+            lines: vec![
+                "seeds: 1\n",
+                "\n",
+                "seed-to-soil map:\n",
+                "1 2 3\n",
+                "\n",
+                "soil-to-water map:\n",
+                "1 2 z\n",
+            ],
+            read: 0,
+        };
+        let mut reader = AlmanacReader::new(BufReader::with_capacity(64, source)).unwrap();
+
+        assert!(reader.next().unwrap().is_ok());
+        assert!(reader.reader.get_ref().read < 7);
+        assert!(matches!(
+            reader.next(),
+            Some(Err(AlmanacParseError::InvalidNumber { line: 7, .. }))
+        ));
+    }
+
+    #[test]
+    fn reader_unreadable_test() {
+        let almanac_def: &[u8] = b"seeds: 1\n\nseed-to-soil map:\n\xff\n";
+        let mut reader = AlmanacReader::new(almanac_def).unwrap();
+
+        assert!(matches!(
+            reader.next(),
+            Some(Err(AlmanacParseError::Unreadable { line: 4, .. }))
+        ));
+    }
+
+    #[test]
+    fn parse_error_annotate_test() {
+        let err = AlmanacParseError::InvalidNumber {
+            line: 4,
+            column: 4,
+            token: "-98".to_string(),
+        };
+
+        assert_eq!(
+            err.annotate("50 -98 2"),
+            format!(
+                "line 4, column 4: expected number from 0 to {}, found '-98'\n  |\n4 | 50 -98 2\n  |    ^^^",
+                Id::MAX
+            )
+        );
+    }
+}
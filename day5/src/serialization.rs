@@ -0,0 +1,197 @@
+use crate::almanac::Almanac;
+use crate::mapper::{Id, Mapper, RangeError};
+use anyhow::Result;
+use serde::de::{DeserializeOwned, Error};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Range of a map, as `destination_start source_start length` line of the almanac.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RangeSchema {
+    pub destination_start: Id,
+    pub source_start: Id,
+    pub length: Id,
+}
+
+/// Map as stored on disk, with the range definitions of `Mapper::ranges`, so maps doing the same thing are stored
+/// the same way.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MapperSchema {
+    pub source: String,
+    pub destination: String,
+    pub ranges: Vec<RangeSchema>,
+}
+
+/// Almanac as stored on disk, with maps in the order they are defined in.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AlmanacSchema {
+    pub seeds: Vec<Id>,
+    pub maps: Vec<MapperSchema>,
+}
+
+impl From<&Mapper> for MapperSchema {
+    fn from(mapper: &Mapper) -> Self {
+        MapperSchema {
+            source: mapper.source.clone(),
+            destination: mapper.destination.clone(),
+            ranges: mapper
+                .ranges()
+                .into_iter()
+                .map(|(destination_start, source_start, length)| RangeSchema {
+                    destination_start,
+                    source_start,
+                    length,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl TryFrom<MapperSchema> for Mapper {
+    type Error = RangeError;
+
+    fn try_from(schema: MapperSchema) -> Result<Self, Self::Error> {
+        Mapper::new(
+            &schema.source,
+            &schema.destination,
+            schema
+                .ranges
+                .into_iter()
+                .map(|r| (r.destination_start, r.source_start, r.length))
+                .collect(),
+        )
+    }
+}
+
+impl From<&Almanac> for AlmanacSchema {
+    fn from(almanac: &Almanac) -> Self {
+        AlmanacSchema {
+            seeds: almanac.seeds.clone(),
+            maps: almanac.maps().iter().map(MapperSchema::from).collect(),
+        }
+    }
+}
+
+impl TryFrom<AlmanacSchema> for Almanac {
+    type Error = RangeError;
+
+    fn try_from(schema: AlmanacSchema) -> Result<Self, Self::Error> {
+        let maps = schema
+            .maps
+            .into_iter()
+            .map(Mapper::try_from)
+            .collect::<Result<Vec<Mapper>, Self::Error>>()?;
+
+        Ok(Almanac::new(schema.seeds, maps))
+    }
+}
+
+impl Serialize for Mapper {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        MapperSchema::from(self).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Mapper {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Mapper::try_from(MapperSchema::deserialize(deserializer)?).map_err(D::Error::custom)
+    }
+}
+
+impl Serialize for Almanac {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        AlmanacSchema::from(self).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Almanac {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Almanac::try_from(AlmanacSchema::deserialize(deserializer)?).map_err(D::Error::custom)
+    }
+}
+
+pub fn to_json<T: Serialize>(value: &T) -> Result<String> {
+    Ok(serde_json::to_string(value)?)
+}
+
+pub fn from_json<T: DeserializeOwned>(json: &str) -> Result<T> {
+    Ok(serde_json::from_str(json)?)
+}
+
+/// Writes the value with bincode, which stores ids as fixed width little endian integers.
+pub fn to_binary<T: Serialize>(value: &T) -> Result<Vec<u8>> {
+    Ok(bincode::serialize(value)?)
+}
+
+pub fn from_binary<T: DeserializeOwned>(bytes: &[u8]) -> Result<T> {
+    Ok(bincode::deserialize(bytes)?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{from_binary, from_json, to_binary, to_json};
+    use crate::almanac::Almanac;
+    use crate::mapper::{Id, Mapper};
+
+    const EXAMPLE: &str = "seeds: 79 14 55 13
+
+seed-to-soil map:
+50 98 2
+52 50 48
+
+soil-to-fertilizer map:
+0 15 37
+37 52 2
+39 0 15
+";
+
+    #[test]
+    fn mapper_json_schema_test() {
+        let mapper = Mapper::new("seed", "soil", vec![(50, 98, 2), (52, 50, 48)]).unwrap();
+        let json = to_json(&mapper).unwrap();
+
+        assert_eq!(
+            json,
+            "{\"source\":\"seed\",\"destination\":\"soil\",\"ranges\":[\
+            {\"destination_start\":52,\"source_start\":50,\"length\":48},\
+            {\"destination_start\":50,\"source_start\":98,\"length\":2}]}"
+        );
+        assert_eq!(from_json::<Mapper>(&json).unwrap(), mapper);
+        assert_eq!(
+            from_binary::<Mapper>(&to_binary(&mapper).unwrap()).unwrap(),
+            mapper
+        );
+    }
+
+    #[test]
+    fn almanac_round_trip_test() {
+        let almanac = Almanac::try_from(EXAMPLE.to_string()).unwrap();
+
+        for decoded in [
+            from_json::<Almanac>(&to_json(&almanac).unwrap()).unwrap(),
+            from_binary::<Almanac>(&to_binary(&almanac).unwrap()).unwrap(),
+        ] {
+            assert_eq!(decoded, almanac);
+            assert_eq!(decoded.to_string(), almanac.to_string());
+        }
+
+        // composed maps are cached and shared the same way as maps read from the almanac
+        let composed = almanac.composed_map("seed", "fertilizer").unwrap();
+        let decoded: Mapper = from_binary(&to_binary(composed).unwrap()).unwrap();
+        assert!(decoded.is_equivalent(composed));
+    }
+
+    #[test]
+    fn deserialize_invalid_range_test() {
+        let json = format!(
+            "{{\"source\":\"seed\",\"destination\":\"soil\",\"ranges\":[\
+            {{\"destination_start\":0,\"source_start\":{},\"length\":2}}]}}",
+            Id::MAX
+        );
+
+        assert!(from_json::<Mapper>(&json)
+            .unwrap_err()
+            .to_string()
+            .starts_with("Range starting at"));
+        assert!(from_json::<Mapper>("{\"source\":\"seed\"}").is_err());
+    }
+}
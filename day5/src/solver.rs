@@ -0,0 +1,139 @@
+use crate::almanac::{Almanac, SeedMode};
+use crate::mapper::Id;
+use crate::validation::Severity;
+use anyhow::{anyhow, Result};
+use aoc_core::Solver;
+
+/// Finds the closest location for the seeds of the almanac, read as individual seeds and as seed ranges.
+pub struct Day5;
+
+impl Day5 {
+    fn closest_location(almanac: &Almanac, mode: SeedMode) -> Result<Id> {
+        // min_location gives no answer without a single chain of maps either, tell that apart from having no seeds
+        almanac.path("seed", "location")?;
+        let (_seed, location) = almanac
+            .min_location(&almanac.seed_ranges(mode)?)
+            .ok_or(anyhow!("No initial seeds in almanac"))?;
+
+        Ok(location)
+    }
+}
+
+impl Solver for Day5 {
+    const DAY: u8 = 5;
+
+    type Input = Almanac;
+    type Answer1 = Id;
+    type Answer2 = Id;
+
+    /// Parses the almanac, refusing almanacs with validation errors as their answers would be wrong.
+    fn parse(input: &str) -> Result<Self::Input> {
+        let almanac = Almanac::from_reader(input.as_bytes())?;
+        if let Some(error) = almanac
+            .validate()
+            .into_iter()
+            .find(|issue| issue.severity == Severity::Error)
+        {
+            Err(anyhow!("Refusing to solve the almanac, {}", error))?
+        }
+
+        Ok(almanac)
+    }
+
+    fn part1(almanac: &Self::Input) -> Result<Self::Answer1> {
+        Self::closest_location(almanac, SeedMode::Individual)
+    }
+
+    fn part2(almanac: &Self::Input) -> Result<Self::Answer2> {
+        Self::closest_location(almanac, SeedMode::Ranges)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Day5;
+    use aoc_core::Solver;
+
+    #[test]
+    fn solver_test() {
+        let almanac = Day5::parse(
+            "seeds: 79 14 55 13
+
+seed-to-location map:
+50 98 2
+52 50 48
+",
+        )
+        .unwrap();
+
+        assert_eq!(Day5::part1(&almanac).unwrap(), 13);
+        assert_eq!(Day5::part2(&almanac).unwrap(), 57);
+
+        let overlapping = Day5::parse(
+            "seeds: 79 14
+
+seed-to-location map:
+50 98 2
+52 97 3
+",
+        );
+        assert!(overlapping
+            .unwrap_err()
+            .to_string()
+            .starts_with("Refusing to solve the almanac, error: seed-to-location map, line 4"));
+    }
+
+    #[test]
+    fn solver_no_answer_test() {
+        let no_seeds = Day5::parse(
+            "seeds:
+
+seed-to-location map:
+50 98 2
+",
+        )
+        .unwrap();
+        assert_eq!(
+            Day5::part1(&no_seeds).unwrap_err().to_string(),
+            "No initial seeds in almanac"
+        );
+
+        let branching = Day5::parse(
+            "seeds: 79 14
+
+seed-to-soil map:
+50 98 2
+
+seed-to-water map:
+50 98 2
+
+soil-to-location map:
+50 98 2
+
+water-to-location map:
+50 98 2
+",
+        )
+        .unwrap();
+        assert!(Day5::part1(&branching)
+            .unwrap_err()
+            .to_string()
+            .starts_with("Several chains of maps lead from seed to location"));
+
+        let unreachable = Day5::parse(
+            "seeds: 79 14
+
+seed-to-soil map:
+50 98 2
+
+water-to-location map:
+50 98 2
+",
+        )
+        .unwrap();
+        assert_eq!(
+            Day5::part2(&unreachable).unwrap_err().to_string(),
+            "No chain of maps leads from seed to location"
+        );
+    }
+}
@@ -0,0 +1,352 @@
+use crate::mapper::{checked_range, Id, Mapper};
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::ops::Range;
+
+/// Range of a map as written in the almanac, before it is turned into a `Mapper`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RangeDefinition {
+    pub line: Option<usize>,
+    pub dst_start: Id,
+    pub src_start: Id,
+    pub len: Id,
+}
+
+impl RangeDefinition {
+    pub fn src(&self) -> Option<Range<Id>> {
+        checked_range(self.src_start, self.len).ok()
+    }
+
+    pub fn dst(&self) -> Option<Range<Id>> {
+        checked_range(self.dst_start, self.len).ok()
+    }
+}
+
+/// Map as written in the almanac, kept around to point diagnostics at the lines it came from.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MapDefinition {
+    pub line: Option<usize>,
+    pub source: String,
+    pub destination: String,
+    pub ranges: Vec<RangeDefinition>,
+}
+
+impl MapDefinition {
+    /// Describes a map built in code rather than read from text.
+    pub fn of(mapper: &Mapper) -> Self {
+        MapDefinition {
+            line: None,
+            source: mapper.source.clone(),
+            destination: mapper.destination.clone(),
+            ranges: mapper
+                .ranges()
+                .into_iter()
+                .map(|(dst_start, src_start, len)| RangeDefinition {
+                    line: None,
+                    dst_start,
+                    src_start,
+                    len,
+                })
+                .collect(),
+        }
+    }
+
+    pub fn name(&self) -> String {
+        format!("{}-to-{}", self.source, self.destination)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// Results are still correct, but the almanac is probably not what its author meant.
+    Warning,
+    /// Results computed from the almanac are wrong or depend on the order of its ranges.
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum IssueKind {
+    /// Map with the same source and destination is already defined at `first_line`.
+    DuplicateMap { first_line: Option<usize> },
+    /// Range does not map anything.
+    ZeroLength,
+    /// Source or destination range end does not fit into `Id`.
+    Overflow { start: Id, len: Id },
+    /// Source range overlaps with the one defined at `other_line`.
+    OverlappingSources {
+        src: Range<Id>,
+        other_src: Range<Id>,
+        other_line: Option<usize>,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Issue {
+    pub severity: Severity,
+    pub map: String,
+    pub line: Option<usize>,
+    pub kind: IssueKind,
+}
+
+impl Display for Issue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        fn at(line: &Option<usize>) -> String {
+            line.map_or("unknown line".to_string(), |l| format!("line {}", l))
+        }
+
+        let severity = match self.severity {
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        };
+        write!(f, "{}: {} map, {}: ", severity, self.map, at(&self.line))?;
+
+        match &self.kind {
+            IssueKind::DuplicateMap { first_line } => {
+                write!(f, "map is already defined at {}", at(first_line))
+            }
+            IssueKind::ZeroLength => write!(f, "range has zero length and maps nothing"),
+            IssueKind::Overflow { start, len } => write!(
+                f,
+                "range starting at {} with length {} goes past {}",
+                start,
+                len,
+                Id::MAX
+            ),
+            IssueKind::OverlappingSources {
+                src,
+                other_src,
+                other_line,
+            } => write!(
+                f,
+                "source range {:?} overlaps with source range {:?} at {}",
+                src,
+                other_src,
+                at(other_line)
+            ),
+        }
+    }
+}
+
+/// Checks map definitions for problems that make the almanac compute wrong results.
+pub fn validate(definitions: &[MapDefinition]) -> Vec<Issue> {
+    let mut issues = vec![];
+
+    let mut first_definitions: HashMap<(&str, &str), &MapDefinition> = HashMap::new();
+    for definition in definitions {
+        let issue = |line, kind| Issue {
+            severity: match kind {
+                IssueKind::ZeroLength => Severity::Warning,
+                _ => Severity::Error,
+            },
+            map: definition.name(),
+            line,
+            kind,
+        };
+
+        match first_definitions.get(&(&definition.source, &definition.destination)) {
+            Some(first) => issues.push(issue(
+                definition.line,
+                IssueKind::DuplicateMap {
+                    first_line: first.line,
+                },
+            )),
+            None => {
+                first_definitions.insert((&definition.source, &definition.destination), definition);
+            }
+        }
+
+        let mut srcs = vec![];
+        for range in definition.ranges.iter() {
+            if range.len == 0 {
+                issues.push(issue(range.line, IssueKind::ZeroLength));
+                continue;
+            }
+
+            let overflowing_start = match (range.src(), range.dst()) {
+                (Some(src), Some(_dst)) => {
+                    srcs.push((src, range.line));
+                    continue;
+                }
+                (None, _) => range.src_start,
+                (_, None) => range.dst_start,
+            };
+            issues.push(issue(
+                range.line,
+                IssueKind::Overflow {
+                    start: overflowing_start,
+                    len: range.len,
+                },
+            ));
+        }
+
+        // after sorting, a range overlaps with some earlier one if and only if it overlaps with the one reaching
+        // furthest
+        srcs.sort_by_key(|(src, line)| (src.start, *line));
+        let mut furthest: Option<&(Range<Id>, Option<usize>)> = None;
+        for curr in srcs.iter() {
+            match furthest {
+                Some((other_src, other_line)) if curr.0.start < other_src.end => {
+                    issues.push(issue(
+                        curr.1,
+                        IssueKind::OverlappingSources {
+                            src: curr.0.clone(),
+                            other_src: other_src.clone(),
+                            other_line: *other_line,
+                        },
+                    ));
+
+                    if curr.0.end > other_src.end {
+                        furthest = Some(curr);
+                    }
+                }
+                _ => furthest = Some(curr),
+            }
+        }
+    }
+
+    issues
+}
+
+#[cfg(test)]
+mod test {
+    use super::{validate, Issue, IssueKind, MapDefinition, RangeDefinition, Severity};
+    use crate::mapper::Id;
+
+    fn map_definition(line: usize, name: (&str, &str), ranges: &[(Id, Id, Id)]) -> MapDefinition {
+        MapDefinition {
+            line: Some(line),
+            source: name.0.to_string(),
+            destination: name.1.to_string(),
+            ranges: ranges
+                .iter()
+                .enumerate()
+                .map(|(idx, &(dst_start, src_start, len))| RangeDefinition {
+                    line: Some(line + idx + 1),
+                    dst_start,
+                    src_start,
+                    len,
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn validate_valid_test() {
+        let definitions = [
+            map_definition(3, ("seed", "soil"), &[(50, 98, 2), (52, 50, 48)]),
+            map_definition(
+                7,
+                ("soil", "fertilizer"),
+                &[(0, 15, 37), (37, 52, 2), (39, 0, 15)],
+            ),
+        ];
+
+        assert_eq!(validate(&definitions), vec![]);
+    }
+
+    #[test]
+    fn validate_ranges_test() {
+        let definitions = [map_definition(
+            3,
+            ("seed", "soil"),
+            &[
+                (50, 98, 2),
+                (52, 50, 48),
+                (0, 10, 0),
+                (0, Id::MAX - 1, 2),
+                (Id::MAX, 0, 1),
+                (0, 90, 20),
+            ],
+        )];
+
+        assert_eq!(
+            validate(&definitions),
+            vec![
+                Issue {
+                    severity: Severity::Warning,
+                    map: "seed-to-soil".to_string(),
+                    line: Some(6),
+                    kind: IssueKind::ZeroLength,
+                },
+                Issue {
+                    severity: Severity::Error,
+                    map: "seed-to-soil".to_string(),
+                    line: Some(7),
+                    kind: IssueKind::Overflow {
+                        start: Id::MAX - 1,
+                        len: 2
+                    },
+                },
+                Issue {
+                    severity: Severity::Error,
+                    map: "seed-to-soil".to_string(),
+                    line: Some(8),
+                    kind: IssueKind::Overflow {
+                        start: Id::MAX,
+                        len: 1
+                    },
+                },
+                Issue {
+                    severity: Severity::Error,
+                    map: "seed-to-soil".to_string(),
+                    line: Some(9),
+                    kind: IssueKind::OverlappingSources {
+                        src: 90..110,
+                        other_src: 50..98,
+                        other_line: Some(5)
+                    },
+                },
+                Issue {
+                    severity: Severity::Error,
+                    map: "seed-to-soil".to_string(),
+                    line: Some(4),
+                    kind: IssueKind::OverlappingSources {
+                        src: 98..100,
+                        other_src: 90..110,
+                        other_line: Some(9)
+                    },
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_duplicate_maps_test() {
+        let definitions = [
+            map_definition(3, ("seed", "soil"), &[(50, 98, 2)]),
+            map_definition(6, ("soil", "water"), &[(50, 98, 2)]),
+            map_definition(9, ("seed", "soil"), &[(52, 50, 48)]),
+        ];
+
+        assert_eq!(
+            validate(&definitions),
+            vec![Issue {
+                severity: Severity::Error,
+                map: "seed-to-soil".to_string(),
+                line: Some(9),
+                kind: IssueKind::DuplicateMap {
+                    first_line: Some(3)
+                },
+            }]
+        );
+    }
+
+    #[test]
+    fn issue_display_test() {
+        let issue = Issue {
+            severity: Severity::Error,
+            map: "seed-to-soil".to_string(),
+            line: Some(9),
+            kind: IssueKind::OverlappingSources {
+                src: 90..110,
+                other_src: 50..98,
+                other_line: None,
+            },
+        };
+
+        assert_eq!(
+            issue.to_string(),
+            "error: seed-to-soil map, line 9: source range 90..110 overlaps with source range 50..98 at unknown line"
+        );
+    }
+}